@@ -1,11 +1,48 @@
-use test_gpu::{triangle::DrawTriangleInit, wnd::Wnd};
+use test_gpu::{
+    camera::bookmark::Bookmarks,
+    record::{Recorder, Replay},
+    triangle::DrawTriangleInit,
+    wnd::Wnd,
+};
 use winit::event_loop::EventLoop;
 
+const BOOKMARKS_FILE: &str = "camera_bookmarks.json";
+const CAMERA_PATH_FILE: &str = "camera_path.json";
+
 fn main() -> anyhow::Result<()> {
     // tracing_subscriber::fmt().init();
     let event_loop = EventLoop::builder().build()?;
-    let app = DrawTriangleInit::new();
-    let mut app = Wnd::new(Box::new(app));
+    let mut init = DrawTriangleInit::new();
+    let mut args = std::env::args().skip(1);
+    let (recorder, replay) = match (args.next().as_deref(), args.next()) {
+        (None, _) => {
+            // files on disk would make recordings depend on them, so only live runs use them
+            init.set_bookmarks(load_bookmarks());
+            init.set_bookmarks_file(BOOKMARKS_FILE);
+            init.set_camera_path_file(CAMERA_PATH_FILE);
+            (None, None)
+        }
+        (Some("record"), Some(path)) => (Some(Recorder::create(path)?), None),
+        (Some("replay"), Some(path)) => (None, Some(Replay::load(path)?)),
+        _ => anyhow::bail!("usage: triangle [record <path> | replay <path>]"),
+    };
+    let mut app = Wnd::new(Box::new(init));
+    if let Some(recorder) = recorder {
+        app.set_recorder(recorder);
+    }
+    if let Some(replay) = replay {
+        app.set_replay(replay);
+    }
     event_loop.run_app(&mut app)?;
     Ok(())
 }
+
+fn load_bookmarks() -> Bookmarks {
+    if !std::path::Path::new(BOOKMARKS_FILE).exists() {
+        return Bookmarks::new();
+    }
+    Bookmarks::load(BOOKMARKS_FILE).unwrap_or_else(|e| {
+        tracing::warn!(?e, "failed to load camera bookmarks");
+        Bookmarks::new()
+    })
+}
@@ -0,0 +1,421 @@
+use crate::transform::{transform_point, transform_vector, TransformMatrix};
+
+/// axis-aligned box; `min` is component-wise no greater than `max`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+impl Aabb {
+    pub fn new(min: [f64; 3], max: [f64; 3]) -> Self {
+        Self { min, max }
+    }
+    /// `None` without points
+    pub fn from_points(points: impl IntoIterator<Item = [f64; 3]>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, p| {
+            aabb.merge(&Self::new(p, p))
+        }))
+    }
+    pub fn center(&self) -> [f64; 3] {
+        [0, 1, 2].map(|i| (self.min[i] + self.max[i]) / 2.)
+    }
+    pub fn half_extents(&self) -> [f64; 3] {
+        [0, 1, 2].map(|i| (self.max[i] - self.min[i]) / 2.)
+    }
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        let (min, max) = (self.min, self.max);
+        [0, 1, 2, 3, 4, 5, 6, 7]
+            .map(|c| [0, 1, 2].map(|i| if c & (1 << i) == 0 { min[i] } else { max[i] }))
+    }
+
+    /// points on a face count as inside
+    pub fn contains_point(&self, p: [f64; 3]) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+    /// touching boxes overlap
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+    pub fn merge(&self, other: &Self) -> Self {
+        Self {
+            min: [0, 1, 2].map(|i| self.min[i].min(other.min[i])),
+            max: [0, 1, 2].map(|i| self.max[i].max(other.max[i])),
+        }
+    }
+    /// Axis-aligned box around the transformed box.
+    ///
+    /// Conservative: a rotated box gets the box around its corners, which is larger than the
+    /// box around the original geometry. `m` must be affine.
+    pub fn transform(&self, m: &TransformMatrix) -> Self {
+        let center = transform_point(m, self.center());
+        let half = self.half_extents();
+        let axes = [[half[0], 0., 0.], [0., half[1], 0.], [0., 0., half[2]]]
+            .map(|axis| transform_vector(m, axis));
+        let extent = [0, 1, 2].map(|i| axes.iter().map(|a| a[i].abs()).sum::<f64>());
+        Self {
+            min: [0, 1, 2].map(|i| center[i] - extent[i]),
+            max: [0, 1, 2].map(|i| center[i] + extent[i]),
+        }
+    }
+    /// sphere through the corners
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center(), length(self.half_extents()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: [f64; 3],
+    pub radius: f64,
+}
+impl BoundingSphere {
+    pub fn new(center: [f64; 3], radius: f64) -> Self {
+        Self { center, radius }
+    }
+    /// Ritter's approximation; at most a few percent larger than the minimal sphere.
+    ///
+    /// `None` without points.
+    pub fn from_points(points: impl IntoIterator<Item = [f64; 3]>) -> Option<Self> {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let farthest_from = |from: [f64; 3]| {
+            points
+                .iter()
+                .copied()
+                .max_by(|a, b| distance(from, *a).total_cmp(&distance(from, *b)))
+                .expect("not empty")
+        };
+        let a = farthest_from(*points.first()?);
+        let b = farthest_from(a);
+        let mut sphere = Self::new(lerp(a, b, 0.5), distance(a, b) / 2.);
+        for p in &points {
+            sphere = sphere.merge_point(*p);
+        }
+        Some(sphere)
+    }
+    pub fn contains_point(&self, p: [f64; 3]) -> bool {
+        distance(self.center, p) <= self.radius
+    }
+    /// touching spheres overlap
+    pub fn intersects(&self, other: &Self) -> bool {
+        distance(self.center, other.center) <= self.radius + other.radius
+    }
+    pub fn merge(&self, other: &Self) -> Self {
+        let d = distance(self.center, other.center);
+        if d + other.radius <= self.radius {
+            return *self;
+        }
+        if d + self.radius <= other.radius {
+            return *other;
+        }
+        let radius = (d + self.radius + other.radius) / 2.;
+        // `d` is positive here, or one sphere would have contained the other
+        let t = (radius - self.radius) / d;
+        Self::new(lerp(self.center, other.center, t), radius)
+    }
+    /// Scales the radius by the largest axis scale of `m`.
+    ///
+    /// Conservative under non-uniform scale. `m` must be affine.
+    pub fn transform(&self, m: &TransformMatrix) -> Self {
+        let scale = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]
+            .map(|axis| length(transform_vector(m, axis)))
+            .into_iter()
+            .fold(0., f64::max);
+        Self::new(transform_point(m, self.center), self.radius * scale)
+    }
+    fn merge_point(&self, p: [f64; 3]) -> Self {
+        self.merge(&Self::new(p, 0.))
+    }
+}
+
+/// box along arbitrary orthonormal axes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obb {
+    pub center: [f64; 3],
+    /// unit length and mutually perpendicular
+    pub axes: [[f64; 3]; 3],
+    pub half_extents: [f64; 3],
+}
+impl Obb {
+    /// Box along the local axes, since mesh vertices are usually authored around them.
+    ///
+    /// Orientation comes from [`Self::transform`]; `None` without points.
+    pub fn from_points(points: impl IntoIterator<Item = [f64; 3]>) -> Option<Self> {
+        Aabb::from_points(points).map(Self::from)
+    }
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|c| {
+            let mut p = self.center;
+            for (i, axis) in self.axes.iter().enumerate() {
+                let sign = if c & (1 << i) == 0 { -1. } else { 1. };
+                for (p, a) in p.iter_mut().zip(axis) {
+                    *p += sign * self.half_extents[i] * a;
+                }
+            }
+            p
+        })
+    }
+
+    /// points on a face count as inside
+    pub fn contains_point(&self, p: [f64; 3]) -> bool {
+        let d = sub(p, self.center);
+        (0..3).all(|i| dot(d, self.axes[i]).abs() <= self.half_extents[i] + TOLERANCE)
+    }
+    /// separating axis test; touching boxes overlap
+    pub fn intersects(&self, other: &Self) -> bool {
+        let mut axes = vec![];
+        axes.extend(self.axes);
+        axes.extend(other.axes);
+        for a in self.axes {
+            for b in other.axes {
+                let axis = cross(a, b);
+                // parallel edges add nothing the face axes have not covered
+                if f64::EPSILON < length(axis) {
+                    axes.push(axis);
+                }
+            }
+        }
+        let offset = sub(other.center, self.center);
+        axes.into_iter().all(|axis| {
+            let reach = self.projected_radius(axis) + other.projected_radius(axis);
+            dot(offset, axis).abs() <= reach + TOLERANCE
+        })
+    }
+    /// Box along `self`'s axes around both boxes.
+    ///
+    /// Conservative unless the two boxes share axes.
+    pub fn merge(&self, other: &Self) -> Self {
+        let corners = self.corners().into_iter().chain(other.corners());
+        Self::fit(self.center, self.axes, corners)
+    }
+    /// Box along the transformed axes around the transformed corners.
+    ///
+    /// Exact for rotation, translation and scale. Shear, such as non-uniform scale of a rotated
+    /// box, turns the box into a parallelepiped; the axes are then re-orthogonalized and the
+    /// result is a conservative box around it.
+    pub fn transform(&self, m: &TransformMatrix) -> Self {
+        let [x, y, _] = self.axes.map(|axis| transform_vector(m, axis));
+        // a flattened axis keeps its old direction; the box is degenerate along it anyway
+        let x = normalize(x).unwrap_or(self.axes[0]);
+        let y = normalize(sub(y, scaled(x, dot(y, x)))).unwrap_or_else(|| perpendicular(x));
+        let corners = self.corners().map(|p| transform_point(m, p));
+        Self::fit(
+            transform_point(m, self.center),
+            [x, y, cross(x, y)],
+            corners,
+        )
+    }
+    /// box along `axes` around `points`, with `origin` as the reference for projecting onto them
+    fn fit(
+        origin: [f64; 3],
+        axes: [[f64; 3]; 3],
+        points: impl IntoIterator<Item = [f64; 3]>,
+    ) -> Self {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for p in points {
+            let d = sub(p, origin);
+            for i in 0..3 {
+                let x = dot(d, axes[i]);
+                min[i] = min[i].min(x);
+                max[i] = max[i].max(x);
+            }
+        }
+        let mut center = origin;
+        for i in 0..3 {
+            let mid = (min[i] + max[i]) / 2.;
+            for (c, a) in center.iter_mut().zip(axes[i]) {
+                *c += mid * a;
+            }
+        }
+        Self {
+            center,
+            axes,
+            half_extents: [0, 1, 2].map(|i| (max[i] - min[i]) / 2.),
+        }
+    }
+    /// axis-aligned box around the corners
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.corners()).expect("eight corners")
+    }
+    /// half the length of the box's shadow on `axis`, in units of `axis`'s length
+    fn projected_radius(&self, axis: [f64; 3]) -> f64 {
+        (0..3)
+            .map(|i| self.half_extents[i] * dot(self.axes[i], axis).abs())
+            .sum()
+    }
+}
+impl From<Aabb> for Obb {
+    fn from(aabb: Aabb) -> Self {
+        Self {
+            center: aabb.center(),
+            axes: [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+            half_extents: aabb.half_extents(),
+        }
+    }
+}
+
+/// slack for the rounding error of projecting onto rotated axes
+const TOLERANCE: f64 = 1e-9;
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+fn length(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+fn scaled(v: [f64; 3], s: f64) -> [f64; 3] {
+    v.map(|x| x * s)
+}
+/// `None` for vectors too short to have a direction
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let len = length(v);
+    (f64::EPSILON < len).then(|| scaled(v, 1. / len))
+}
+/// some unit vector perpendicular to the unit vector `v`
+fn perpendicular(v: [f64; 3]) -> [f64; 3] {
+    // crossing with the least aligned world axis stays far from zero
+    let i = (0..3)
+        .min_by(|&a, &b| v[a].abs().total_cmp(&v[b].abs()))
+        .expect("three components");
+    let mut axis = [0.; 3];
+    axis[i] = 1.;
+    normalize(cross(v, axis)).expect("not parallel")
+}
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    length(sub(a, b))
+}
+fn lerp(a: [f64; 3], b: [f64; 3], t: f64) -> [f64; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::transform::{rotate, scale, translate, Quaternion, Trs};
+
+    use super::*;
+
+    fn cube() -> Vec<[f64; 3]> {
+        Aabb::new([-0.5; 3], [0.5; 3]).corners().to_vec()
+    }
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_aabb() {
+        let aabb = Aabb::from_points([[1., -2., 0.], [-1., 3., 0.5], [0., 0., -4.]]).unwrap();
+        assert_eq!(aabb, Aabb::new([-1., -2., -4.], [1., 3., 0.5]));
+        assert_eq!(Aabb::from_points([]), None);
+
+        let a = Aabb::new([0.; 3], [1.; 3]);
+        let b = Aabb::new([1., 0.5, 0.5], [2.; 3]);
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&Aabb::new([1.001, 0., 0.], [2.; 3])));
+        assert_eq!(a.merge(&b), Aabb::new([0.; 3], [2.; 3]));
+
+        // rotating 45° about Y widens the box by the diagonal
+        let m = translate([5., 0., 0.]).mul_matrix_square(&rotate([0., 1., 0.], PI / 4.));
+        let rotated = Aabb::from_points(cube()).unwrap().transform(&m);
+        let half = 2_f64.sqrt() / 2.;
+        assert_close(rotated.min, [5. - half, -0.5, -half]);
+        assert_close(rotated.max, [5. + half, 0.5, half]);
+        for p in cube() {
+            assert!(rotated.contains_point(transform_point(&m, p)));
+        }
+    }
+
+    #[test]
+    fn test_sphere() {
+        let sphere = BoundingSphere::from_points(cube()).unwrap();
+        for p in cube() {
+            assert!(sphere.radius - distance(sphere.center, p) > -1e-9);
+        }
+        assert!(sphere.radius < 3_f64.sqrt() / 2. * 1.05);
+
+        let a = BoundingSphere::new([0.; 3], 1.);
+        let b = BoundingSphere::new([3., 0., 0.], 1.);
+        assert!(!a.intersects(&b));
+        let merged = a.merge(&b);
+        assert_close(merged.center, [1.5, 0., 0.]);
+        assert_eq!(merged.radius, 2.5);
+        assert_eq!(a.merge(&BoundingSphere::new([0.5, 0., 0.], 0.2)), a);
+
+        let m = translate([0., 1., 0.]).mul_matrix_square(&scale([1., 3., 2.]));
+        let moved = a.transform(&m);
+        assert_eq!(moved, BoundingSphere::new([0., 1., 0.], 3.));
+    }
+
+    #[test]
+    fn test_obb() {
+        let trs = Trs {
+            translation: [1., 2., 3.],
+            rotation: Quaternion::from_axis_angle([1., 1., 0.], 0.8),
+            scale: [2., 1., 0.5],
+        };
+        let m = trs.matrix();
+        let obb = Obb::from_points(cube()).unwrap().transform(&m);
+        assert_close(obb.half_extents, [1., 0.5, 0.25]);
+        for p in cube() {
+            assert!(obb.contains_point(transform_point(&m, p)));
+        }
+        assert!(!obb.contains_point(transform_point(&m, [0.6, 0., 0.])));
+        // the transformed AABB is the box around the same corners
+        let aabb = Aabb::from_points(cube()).unwrap().transform(&m);
+        assert_close(obb.aabb().min, aabb.min);
+        assert_close(obb.aabb().max, aabb.max);
+
+        // diamond off a box corner: the AABBs overlap but the boxes do not
+        let m = translate([1.1, 1.1, 0.]).mul_matrix_square(&rotate([0., 0., 1.], PI / 4.));
+        let diamond = Obb::from_points(cube()).unwrap().transform(&m);
+        let unit = Obb::from_points(cube()).unwrap();
+        assert!(diamond.aabb().intersects(&unit.aabb()));
+        assert!(!diamond.intersects(&unit));
+        let closer = Obb {
+            center: [0.7, 0.7, 0.],
+            ..diamond
+        };
+        assert!(closer.intersects(&unit));
+
+        let merged = unit.merge(&closer);
+        for p in unit.corners().into_iter().chain(closer.corners()) {
+            assert!(merged.contains_point(p));
+        }
+    }
+
+    #[test]
+    fn test_obb_shear() {
+        // stretching a rotated box skews it into a rhombus
+        let m = scale([3., 1., 1.]).mul_matrix_square(&rotate([0., 0., 1.], PI / 4.));
+        let obb = Obb::from_points(cube()).unwrap().transform(&m);
+        for i in 0..3 {
+            assert!((length(obb.axes[i]) - 1.).abs() < 1e-9);
+            for j in 0..i {
+                assert!(dot(obb.axes[i], obb.axes[j]).abs() < 1e-9);
+            }
+        }
+        for p in cube() {
+            assert!(obb.contains_point(transform_point(&m, p)));
+        }
+        // no looser than the axis-aligned box, which is the best fit for this rhombus
+        let aabb = Aabb::from_points(cube()).unwrap().transform(&m);
+        let volume = |h: [f64; 3]| h[0] * h[1] * h[2];
+        assert!(volume(obb.half_extents) <= volume(aabb.half_extents()) + 1e-9);
+    }
+}
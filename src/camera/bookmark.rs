@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    path::{CameraPath, Keyframe, PathPlayback, Spline},
+    Camera,
+};
+
+/// saved viewpoints by slot number
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    slots: BTreeMap<u8, Camera>,
+}
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn set(&mut self, slot: u8, camera: Camera) {
+        self.slots.insert(slot, camera);
+    }
+    pub fn get(&self, slot: u8) -> Option<&Camera> {
+        self.slots.get(&slot)
+    }
+    pub fn remove(&mut self, slot: u8) -> Option<Camera> {
+        self.slots.remove(&slot)
+    }
+}
+
+/// animated flight from one camera to a bookmarked one
+#[derive(Debug, Clone)]
+pub struct Transition {
+    playback: PathPlayback,
+    target: Camera,
+}
+impl Transition {
+    /// `duration` in seconds
+    pub fn new(from: &Camera, target: Camera, duration: f64) -> Self {
+        let mut path = CameraPath::new(Spline::CatmullRom);
+        path.insert(Keyframe {
+            time: 0.,
+            pose: from.pose(),
+        });
+        path.insert(Keyframe {
+            time: duration,
+            pose: target.pose(),
+        });
+        Self {
+            playback: PathPlayback::new(path, 1.),
+            target,
+        }
+    }
+    /// Moves `camera` along the transition; returns `false` once it has landed on the target.
+    pub fn apply(&mut self, camera: &mut Camera, now: std::time::Instant) -> bool {
+        if self.playback.is_finished(now) {
+            *camera = self.target.clone();
+            return false;
+        }
+        if let Some(pose) = self.playback.pose(now) {
+            camera.set_pose(pose);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::camera::{projection::Projection, OrientationMode};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut camera = Camera::new();
+        camera.set_position([1., 2., 3.]);
+        camera.set_yaw(0.4);
+        camera.set_speed(7.);
+        camera.set_sensitivity(0.3);
+        camera.set_projection(Projection::perspective(0.6, 0.1, None));
+        camera.set_orientation_mode(OrientationMode::Quaternion);
+        camera.roll(0.2);
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.set(1, camera.clone());
+        bookmarks.set(9, Camera::new());
+
+        let json = serde_json::to_string(&bookmarks).unwrap();
+        let loaded: Bookmarks = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, bookmarks);
+        assert_eq!(loaded.get(1), Some(&camera));
+        assert_eq!(loaded.get(2), None);
+    }
+
+    #[test]
+    fn test_transition() {
+        let from = Camera::new();
+        let mut target = Camera::new();
+        target.set_position([4., 0., 0.]);
+        target.set_sensitivity(0.3);
+        let mut transition = Transition::new(&from, target.clone(), 1.);
+
+        let start = Instant::now();
+        let mut camera = from.clone();
+        assert!(transition.apply(&mut camera, start));
+        assert!(transition.apply(&mut camera, start + Duration::from_millis(500)));
+        let x = camera.position()[0];
+        assert!(0. < x && x < 4.);
+        assert!(!transition.apply(&mut camera, start + Duration::from_secs(1)));
+        assert_eq!(camera, target);
+    }
+}
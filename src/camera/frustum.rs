@@ -0,0 +1,210 @@
+use crate::transform::{DepthRange, TransformMatrix};
+
+/// slack for the rounding error of extracting planes from a matrix product
+const TOLERANCE: f64 = 1e-9;
+
+/// `normal · p + offset`, non-negative on the inner side
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: [f64; 3],
+    pub offset: f64,
+}
+impl Plane {
+    fn from_row(row: [f64; 4]) -> Self {
+        let len = (row[0] * row[0] + row[1] * row[1] + row[2] * row[2]).sqrt();
+        // an infinite far plane has no normal; its offset alone keeps everything inside
+        let len = if len < f64::EPSILON { 1. } else { len };
+        Self {
+            normal: [row[0] / len, row[1] / len, row[2] / len],
+            offset: row[3] / len,
+        }
+    }
+    pub fn distance(&self, point: [f64; 3]) -> f64 {
+        let n = self.normal;
+        n[0] * point[0] + n[1] * point[1] + n[2] * point[2] + self.offset
+    }
+}
+
+/// the six planes bounding what a view-projection matrix can see
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Plane; 6],
+}
+impl Frustum {
+    /// `depth` is the NDC depth range the matrix projects into
+    pub fn new(view_projection: &TransformMatrix, depth: DepthRange) -> Self {
+        let m = view_projection.clone().into_buffer();
+        let row = |i: usize| [m[i * 4], m[i * 4 + 1], m[i * 4 + 2], m[i * 4 + 3]];
+        let combine = |a: [f64; 4], sign: f64, b: [f64; 4]| {
+            Plane::from_row([
+                a[0] + sign * b[0],
+                a[1] + sign * b[1],
+                a[2] + sign * b[2],
+                a[3] + sign * b[3],
+            ])
+        };
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let near = match depth {
+            DepthRange::NegOneToOne => combine(w, 1., z),
+            DepthRange::ZeroToOne => Plane::from_row(z),
+        };
+        let planes = [
+            combine(w, 1., x),
+            combine(w, -1., x),
+            combine(w, 1., y),
+            combine(w, -1., y),
+            near,
+            combine(w, -1., z),
+        ];
+        Self { planes }
+    }
+    pub fn planes(&self) -> &[Plane; 6] {
+        &self.planes
+    }
+
+    /// points on a plane count as inside
+    pub fn contains_point(&self, point: [f64; 3]) -> bool {
+        self.planes.iter().all(|p| -TOLERANCE <= p.distance(point))
+    }
+    /// conservative; a sphere just outside a frustum corner still passes
+    pub fn intersects_sphere(&self, center: [f64; 3], radius: f64) -> bool {
+        self.planes
+            .iter()
+            .all(|p| -radius - TOLERANCE <= p.distance(center))
+    }
+    /// conservative; a box just outside a frustum corner still passes
+    pub fn intersects_aabb(&self, min: [f64; 3], max: [f64; 3]) -> bool {
+        self.planes.iter().all(|p| {
+            let mut farthest = [0.; 3];
+            for i in 0..3 {
+                farthest[i] = if 0. <= p.normal[i] { max[i] } else { min[i] };
+            }
+            -TOLERANCE <= p.distance(farthest)
+        })
+    }
+}
+
+/// objects drawn and culled in a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+impl CullStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn count(&mut self, is_visible: bool) {
+        if is_visible {
+            self.drawn += 1;
+        } else {
+            self.culled += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::transform::{
+        look_at, orthographic, perspective, perspective_infinite, reverse_depth, ClipSpace,
+    };
+
+    use super::*;
+
+    /// 90° fov looking down -Z from the origin, near 1 and far 10
+    fn frustum(clip: ClipSpace) -> Frustum {
+        let projection = perspective(PI / 2., 1., 1., 10., clip);
+        let view = look_at([0., 0., 0.], [0., 0., -1.], [0., 1., 0.]);
+        Frustum::new(&projection.mul_matrix_square(&view), clip.depth)
+    }
+
+    #[test]
+    fn test_points() {
+        for clip in [ClipSpace::WGPU, ClipSpace::OPENGL] {
+            let f = frustum(clip);
+            assert!(f.contains_point([0., 0., -5.]));
+            // planes are inclusive
+            assert!(f.contains_point([0., 0., -1.]));
+            assert!(f.contains_point([0., 0., -10.]));
+            assert!(f.contains_point([5., 5., -5.]));
+            assert!(!f.contains_point([0., 0., -0.999]));
+            assert!(!f.contains_point([0., 0., -10.001]));
+            assert!(!f.contains_point([5.001, 0., -5.]));
+            assert!(!f.contains_point([0., -5.001, -5.]));
+            assert!(!f.contains_point([0., 0., 5.]));
+        }
+    }
+
+    #[test]
+    fn test_spheres() {
+        let f = frustum(ClipSpace::WGPU);
+        assert!(f.intersects_sphere([0., 0., -5.], 0.5));
+        // touching from outside
+        assert!(f.intersects_sphere([0., 0., -11.], 1.));
+        assert!(!f.intersects_sphere([0., 0., -11.001], 1.));
+        assert!(f.intersects_sphere([0., 0., 0.], 1.));
+        assert!(!f.intersects_sphere([0., 0., 0.], 0.999));
+        let side = 2_f64.sqrt();
+        assert!(f.intersects_sphere([7., 0., -5.], side));
+        assert!(!f.intersects_sphere([7., 0., -5.], side - 0.001));
+    }
+
+    #[test]
+    fn test_aabbs() {
+        let f = frustum(ClipSpace::WGPU);
+        assert!(f.intersects_aabb([-1., -1., -6.], [1., 1., -4.]));
+        // enclosing the whole frustum
+        assert!(f.intersects_aabb([-100., -100., -100.], [100., 100., 100.]));
+        // sharing a face with the far plane
+        assert!(f.intersects_aabb([-1., -1., -12.], [1., 1., -10.]));
+        assert!(!f.intersects_aabb([-1., -1., -12.], [1., 1., -10.001]));
+        // behind the camera
+        assert!(!f.intersects_aabb([-1., -1., 1.], [1., 1., 2.]));
+        // beside the left plane
+        assert!(!f.intersects_aabb([-9., -1., -6.], [-6.001, 1., -4.]));
+    }
+
+    #[test]
+    fn test_reversed_infinite() {
+        let clip = ClipSpace::WGPU;
+        let projection = reverse_depth(clip.depth).mul_matrix_square(&perspective_infinite(
+            PI / 2.,
+            1.,
+            1.,
+            clip,
+        ));
+        let f = Frustum::new(&projection, clip.depth);
+        assert!(f.contains_point([0., 0., -1.]));
+        assert!(f.contains_point([0., 0., -1e9]));
+        assert!(!f.contains_point([0., 0., -0.999]));
+        assert!(!f.contains_point([1e9 + 1., 0., -1e9]));
+    }
+
+    #[test]
+    fn test_orthographic() {
+        let clip = ClipSpace::WGPU;
+        let f = Frustum::new(&orthographic(-2., 2., 1., -1., 1., 3., clip), clip.depth);
+        assert!(f.contains_point([2., 1., -3.]));
+        assert!(!f.contains_point([2.001, 0., -2.]));
+        assert!(f.intersects_aabb([2., -5., -2.], [3., -1., 0.]));
+        assert!(!f.intersects_aabb([2., -5., -2.], [3., -1.001, 0.]));
+    }
+
+    #[test]
+    fn test_cull_stats() {
+        let f = frustum(ClipSpace::WGPU);
+        let mut stats = CullStats::new();
+        for z in [-5., 5., -20., -2.] {
+            stats.count(f.intersects_sphere([0., 0., z], 0.5));
+        }
+        assert_eq!(
+            stats,
+            CullStats {
+                drawn: 2,
+                culled: 2
+            }
+        );
+    }
+}
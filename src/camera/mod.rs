@@ -0,0 +1,711 @@
+use std::f64::consts::PI;
+
+use math::vector::VectorMut;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::Position2D,
+    transform::{
+        change_of_space, inverse, look_at, transform_point, Quaternion, Ray, TransformMatrix,
+    },
+    WndSize,
+};
+
+use path::CameraPose;
+use projection::Projection;
+
+pub mod bookmark;
+pub mod frustum;
+pub mod orbit;
+pub mod path;
+pub mod projection;
+
+const TRI_PERIOD: f64 = 2. * PI;
+const NEAR_PERPENDICULAR: f64 = PI / 2. - 0.001;
+const SPRINT_FACTOR: f64 = 3.;
+const SLOW_FACTOR: f64 = 0.25;
+
+/// input-driven camera that the renderer can swap at runtime
+pub trait CameraController: core::fmt::Debug + Sync + Send {
+    fn view_matrix(&self) -> TransformMatrix;
+    fn position(&self) -> [f64; 3];
+    fn projection(&self) -> Projection;
+    fn set_projection(&mut self, projection: Projection);
+    fn rotate(&mut self, movement: RotationalMovement);
+    fn translate(&mut self, movement: TranslationalMovement, elapsed: f64);
+    /// per-frame upkeep such as smoothing
+    fn advance(&mut self, _elapsed: f64) {}
+    /// positive brings the scene closer
+    fn zoom(&mut self, offset: f64);
+    /// drag in screen space
+    fn pan(&mut self, _offset: Position2D) {}
+    /// fits the box spanning `min` to `max` into a view `aspect` times as wide as it is tall
+    fn frame(&mut self, min: [f64; 3], max: [f64; 3], aspect: f64);
+    /// world-space ray under the cursor
+    fn screen_ray(&self, cursor: Position2D, wnd_size: WndSize) -> Option<Ray> {
+        screen_ray(cursor, wnd_size, &self.view_matrix(), &self.projection())
+    }
+}
+
+/// Unprojects a cursor position into a world-space ray starting on the near plane.
+///
+/// `None` if the view and projection are singular.
+pub fn screen_ray(
+    cursor: Position2D,
+    wnd_size: WndSize,
+    view: &TransformMatrix,
+    projection: &Projection,
+) -> Option<Ray> {
+    let (width, height) = (wnd_size.width as f64, wnd_size.height as f64);
+    let clip_to_world = inverse(&projection.matrix(width / height).mul_matrix_square(view))?;
+    let x = 2. * cursor.x / width - 1.;
+    let y = 1. - 2. * cursor.y / height;
+    let origin = transform_point(&clip_to_world, [x, y, projection.near_depth()]);
+    let mut direction = transform_point(&clip_to_world, [x, y, projection.mid_depth()]);
+    direction.sub(&origin);
+    direction.normalize();
+    Some(Ray { origin, direction })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    speed: f64,
+    position: [f64; 3],
+    sensitivity: f64,
+    sensitivity_curve: SensitivityCurve,
+    invert_y: bool,
+    /// seconds for look input to settle to about 37% of what is left
+    look_smoothing: Option<f64>,
+    /// smoothed look input not applied yet
+    #[serde(skip)]
+    pending_look: RotationalMovement,
+    inertia: Option<Inertia>,
+    #[serde(skip)]
+    velocity: [f64; 3],
+    yaw: f64,
+    pitch: f64,
+    orientation_mode: OrientationMode,
+    /// maps view space onto world space in [`OrientationMode::Quaternion`]
+    attitude: Quaternion,
+    projection: Projection,
+}
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            speed: 2.5,
+            position: [0., 0., 0.],
+            sensitivity: 0.1,
+            sensitivity_curve: SensitivityCurve::Linear,
+            invert_y: false,
+            look_smoothing: None,
+            pending_look: RotationalMovement { yaw: 0., pitch: 0. },
+            inertia: None,
+            velocity: [0.; 3],
+            pitch: 0.,
+            yaw: -PI / 2.,
+            orientation_mode: OrientationMode::YawPitch,
+            attitude: Quaternion::IDENTITY,
+            projection: Projection::default(),
+        }
+    }
+    pub fn set_speed(&mut self, v: f64) {
+        self.speed = v;
+    }
+    pub fn set_sensitivity(&mut self, v: f64) {
+        self.sensitivity = v;
+    }
+    pub fn set_sensitivity_curve(&mut self, v: SensitivityCurve) {
+        self.sensitivity_curve = v;
+    }
+    pub fn set_invert_y(&mut self, v: bool) {
+        self.invert_y = v;
+    }
+    pub fn invert_y(&self) -> bool {
+        self.invert_y
+    }
+    /// `None`, zero or less turns at once, applying any look input still pending
+    pub fn set_look_smoothing(&mut self, v: Option<f64>) {
+        let v = v.filter(|smoothing| 0. < *smoothing);
+        self.look_smoothing = v;
+        if v.is_none() {
+            let pending = self.pending_look;
+            self.pending_look = RotationalMovement { yaw: 0., pitch: 0. };
+            self.turn(pending);
+        }
+    }
+    /// `None` starts and stops at once
+    pub fn set_inertia(&mut self, v: Option<Inertia>) {
+        self.inertia = v;
+        if v.is_none() {
+            self.velocity = [0.; 3];
+        }
+    }
+    pub fn velocity(&self) -> [f64; 3] {
+        self.velocity
+    }
+    pub fn set_position(&mut self, v: [f64; 3]) {
+        self.position = v;
+    }
+    pub fn position(&self) -> [f64; 3] {
+        self.position
+    }
+    pub fn set_yaw(&mut self, yaw: f64) {
+        self.yaw = yaw % TRI_PERIOD;
+    }
+    pub fn set_pitch(&mut self, pitch: f64) {
+        self.pitch = (pitch % TRI_PERIOD).clamp(-NEAR_PERPENDICULAR, NEAR_PERPENDICULAR);
+    }
+    pub fn yaw(&self) -> f64 {
+        match self.orientation_mode {
+            OrientationMode::YawPitch => self.yaw,
+            OrientationMode::Quaternion => {
+                let facing = self.facing();
+                facing[2].atan2(facing[0])
+            }
+        }
+    }
+    pub fn pitch(&self) -> f64 {
+        match self.orientation_mode {
+            OrientationMode::YawPitch => self.pitch,
+            OrientationMode::Quaternion => self.facing()[1].clamp(-1., 1.).asin(),
+        }
+    }
+    /// Switching to [`OrientationMode::YawPitch`] drops any roll and clamps the pitch.
+    pub fn set_orientation_mode(&mut self, mode: OrientationMode) {
+        match (self.orientation_mode, mode) {
+            (OrientationMode::YawPitch, OrientationMode::Quaternion) => {
+                self.attitude = attitude(self.yaw, self.pitch);
+            }
+            (OrientationMode::Quaternion, OrientationMode::YawPitch) => {
+                let (yaw, pitch) = (self.yaw(), self.pitch());
+                self.set_yaw(yaw);
+                self.set_pitch(pitch);
+            }
+            _ => (),
+        }
+        self.orientation_mode = mode;
+    }
+    pub fn orientation_mode(&self) -> OrientationMode {
+        self.orientation_mode
+    }
+    pub fn set_attitude(&mut self, v: Quaternion) {
+        self.attitude = v.normalize();
+    }
+    pub fn attitude(&self) -> Quaternion {
+        match self.orientation_mode {
+            OrientationMode::YawPitch => attitude(self.yaw, self.pitch),
+            OrientationMode::Quaternion => self.attitude,
+        }
+    }
+    pub fn facing(&self) -> [f64; 3] {
+        match self.orientation_mode {
+            OrientationMode::YawPitch => facing(self.yaw, self.pitch),
+            OrientationMode::Quaternion => self.attitude.rotate([0., 0., -1.]),
+        }
+    }
+    /// screen up in world space
+    pub fn up(&self) -> [f64; 3] {
+        self.attitude().rotate([0., 1., 0.])
+    }
+    /// screen right in world space
+    pub fn right(&self) -> [f64; 3] {
+        self.attitude().rotate([1., 0., 0.])
+    }
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            position: self.position,
+            yaw: self.yaw(),
+            pitch: self.pitch(),
+            fov: self.projection.fov(),
+        }
+    }
+    pub fn set_pose(&mut self, pose: CameraPose) {
+        self.position = pose.position;
+        self.set_yaw(pose.yaw);
+        self.set_pitch(pose.pitch);
+        self.attitude = attitude(self.yaw, self.pitch);
+        if let Some(fov) = pose.fov {
+            self.projection.set_fov(fov);
+        }
+    }
+
+    pub fn zoom(&mut self, offset: f64) {
+        self.projection.zoom(offset);
+    }
+    pub fn set_projection(&mut self, v: Projection) {
+        self.projection = v;
+    }
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Look input goes through the sensitivity curve and, if enabled, the look smoothing.
+    pub fn rotate(&mut self, movement: RotationalMovement) {
+        let pitch = if self.invert_y {
+            -movement.pitch
+        } else {
+            movement.pitch
+        };
+        let movement = RotationalMovement {
+            yaw: self.sensitivity_curve.apply(movement.yaw) * self.sensitivity,
+            pitch: self.sensitivity_curve.apply(pitch) * self.sensitivity,
+        };
+        match self.look_smoothing {
+            Some(_) => {
+                self.pending_look.yaw += movement.yaw;
+                self.pending_look.pitch += movement.pitch;
+            }
+            None => self.turn(movement),
+        }
+    }
+    /// applies the share of smoothed look input that is due after `elapsed` seconds
+    pub fn advance(&mut self, elapsed: f64) {
+        let Some(smoothing) = self.look_smoothing else {
+            return;
+        };
+        // a non-positive time from deserialization settles at once
+        let portion = if 0. < smoothing {
+            1. - (-elapsed / smoothing).exp()
+        } else {
+            1.
+        };
+        let step = RotationalMovement {
+            yaw: self.pending_look.yaw * portion,
+            pitch: self.pending_look.pitch * portion,
+        };
+        self.pending_look.yaw -= step.yaw;
+        self.pending_look.pitch -= step.pitch;
+        self.turn(step);
+    }
+    /// `movement` in radians
+    fn turn(&mut self, movement: RotationalMovement) {
+        if self.orientation_mode == OrientationMode::Quaternion {
+            // about the camera's own axes so that nothing locks up
+            let yaw = Quaternion::from_axis_angle([0., 1., 0.], -movement.yaw);
+            let pitch = Quaternion::from_axis_angle([1., 0., 0.], movement.pitch);
+            self.attitude = (self.attitude * yaw * pitch).normalize();
+            return;
+        }
+        self.set_pitch(self.pitch + movement.pitch);
+        self.set_yaw(self.yaw + movement.yaw);
+    }
+    /// positive tilts the view clockwise; ignored in [`OrientationMode::YawPitch`]
+    pub fn roll(&mut self, angle: f64) {
+        if self.orientation_mode == OrientationMode::YawPitch {
+            return;
+        }
+        let roll = Quaternion::from_axis_angle([0., 0., 1.], -angle);
+        self.attitude = (self.attitude * roll).normalize();
+    }
+    /// Moves for `elapsed` seconds, easing in and out of the intended velocity if inertia is on.
+    pub fn translate(&mut self, movement: TranslationalMovement, elapsed: f64) {
+        let target = self.intended_velocity(movement);
+        let Some(inertia) = self.inertia else {
+            let mut translation = target;
+            translation.mul(elapsed);
+            self.position.add(&translation);
+            return;
+        };
+        let rate = if target == [0.; 3] {
+            inertia.damping
+        } else {
+            inertia.acceleration
+        };
+        // exact solution of `dv/dt = rate * (target - v)` so that frame rate does not matter;
+        // a rate of zero or less never decays and moves at the current velocity
+        let (decay, settling_time) = if 0. < rate {
+            let decay = (-rate * elapsed).exp();
+            (decay, (1. - decay) / rate)
+        } else {
+            (1., elapsed)
+        };
+        let mut excess = self.velocity;
+        excess.sub(&target);
+        let mut translation = target;
+        translation.mul(elapsed);
+        let mut settling = excess;
+        settling.mul(settling_time);
+        translation.add(&settling);
+        self.position.add(&translation);
+        excess.mul(decay);
+        self.velocity = target;
+        self.velocity.add(&excess);
+    }
+    fn intended_velocity(&self, movement: TranslationalMovement) -> [f64; 3] {
+        let speed = self.speed * movement.pace_factor();
+        let surge = match movement.surge {
+            None => 0.,
+            Some(Surge::Forward) => 1.,
+            Some(Surge::Backward) => -1.,
+        };
+        let sway = match movement.sway {
+            None => 0.,
+            Some(Sway::Left) => -1.,
+            Some(Sway::Right) => 1.,
+        };
+        let heave = match movement.heave {
+            None => 0.,
+            Some(Heave::Down) => -1.,
+            Some(Heave::Up) => 1.,
+        };
+        if self.orientation_mode == OrientationMode::Quaternion {
+            // along the camera's own axes rather than the horizon
+            let mut velocity = [sway, heave, -surge];
+            if velocity == [0.; 3] {
+                return velocity;
+            }
+            velocity.set_mag(speed);
+            return self.attitude.rotate(velocity);
+        }
+        let horizontal = || {
+            if movement.sway.is_none() && movement.surge.is_none() {
+                return None;
+            }
+            let facing = self.facing();
+            let sway = {
+                let mut direction = [-facing[2], 0., facing[0]];
+                direction.mul(sway);
+                direction
+            };
+            let surge = {
+                let mut direction = [facing[0], 0., facing[2]];
+                direction.mul(surge);
+                direction
+            };
+            let mut horizontal = sway;
+            horizontal.add(&surge);
+            horizontal.set_mag(speed);
+            Some(horizontal)
+        };
+        let horizontal = horizontal();
+
+        let vertical = || {
+            movement.heave?;
+            let mut vertical = [0., heave, 0.];
+            vertical.set_mag(speed);
+            Some(vertical)
+        };
+        let vertical = vertical();
+
+        match (horizontal, vertical) {
+            (None, None) => [0.; 3],
+            (None, Some(x)) => x,
+            (Some(x), None) => x,
+            (Some(a), Some(b)) => {
+                let mut x = a;
+                x.add(&b);
+                x
+            }
+        }
+    }
+
+    pub fn view_matrix(&self) -> TransformMatrix {
+        if self.orientation_mode == OrientationMode::Quaternion {
+            let mut back = self.facing();
+            back.mul(-1.);
+            return change_of_space(self.right(), self.up(), back, self.position);
+        }
+        let mut at = self.position;
+        at.add(&self.facing());
+        look_at(self.position, at, [0., 1., 0.])
+    }
+}
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl CameraController for Camera {
+    fn view_matrix(&self) -> TransformMatrix {
+        Camera::view_matrix(self)
+    }
+    fn position(&self) -> [f64; 3] {
+        Camera::position(self)
+    }
+    fn projection(&self) -> Projection {
+        Camera::projection(self)
+    }
+    fn set_projection(&mut self, projection: Projection) {
+        Camera::set_projection(self, projection)
+    }
+    fn rotate(&mut self, movement: RotationalMovement) {
+        Camera::rotate(self, movement)
+    }
+    fn translate(&mut self, movement: TranslationalMovement, elapsed: f64) {
+        Camera::translate(self, movement, elapsed)
+    }
+    fn advance(&mut self, elapsed: f64) {
+        Camera::advance(self, elapsed)
+    }
+    fn zoom(&mut self, offset: f64) {
+        Camera::zoom(self, offset)
+    }
+    fn frame(&mut self, min: [f64; 3], max: [f64; 3], aspect: f64) {
+        let (center, radius) = bounding_sphere(min, max);
+        let mut back = self.facing();
+        back.mul(-self.projection.frame(radius, aspect));
+        let mut position = center;
+        position.add(&back);
+        self.position = position;
+    }
+}
+
+/// maps raw look input before sensitivity is applied
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SensitivityCurve {
+    Linear,
+    /// raises the magnitude to this power so that slow motion stays precise
+    Power(f64),
+}
+impl SensitivityCurve {
+    pub fn apply(&self, v: f64) -> f64 {
+        match self {
+            SensitivityCurve::Linear => v,
+            SensitivityCurve::Power(exponent) => v.abs().powf(*exponent).copysign(v),
+        }
+    }
+}
+
+/// rates per second at which velocity approaches its intended value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Inertia {
+    /// while movement keys are held
+    pub acceleration: f64,
+    /// after they are released
+    pub damping: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrientationMode {
+    /// pitch stops short of straight up and down; no roll
+    YawPitch,
+    /// unrestricted, including roll
+    Quaternion,
+}
+
+/// turns view-space -Z onto the facing of `yaw` and `pitch`
+fn attitude(yaw: f64, pitch: f64) -> Quaternion {
+    let yaw = Quaternion::from_axis_angle([0., 1., 0.], -yaw - PI / 2.);
+    let pitch = Quaternion::from_axis_angle([1., 0., 0.], pitch);
+    yaw * pitch
+}
+fn facing(yaw: f64, pitch: f64) -> [f64; 3] {
+    [
+        yaw.cos() * pitch.cos(),
+        pitch.sin(),
+        yaw.sin() * pitch.cos(),
+    ]
+}
+fn bounding_sphere(min: [f64; 3], max: [f64; 3]) -> ([f64; 3], f64) {
+    let mut center = min;
+    center.add(&max);
+    center.mul(0.5);
+    let mut diagonal = max;
+    diagonal.sub(&min);
+    let radius = diagonal.iter().map(|x| x * x).sum::<f64>().sqrt() / 2.;
+    (center, radius)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TranslationalMovement {
+    pub surge: Option<Surge>,
+    pub sway: Option<Sway>,
+    pub heave: Option<Heave>,
+    pub pace: Option<Pace>,
+}
+impl TranslationalMovement {
+    /// scale on the camera speed
+    pub fn pace_factor(&self) -> f64 {
+        match self.pace {
+            None => 1.,
+            Some(Pace::Sprint) => SPRINT_FACTOR,
+            Some(Pace::Slow) => SLOW_FACTOR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Surge {
+    Forward,
+    Backward,
+}
+#[derive(Debug, Clone, Copy)]
+pub enum Sway {
+    Left,
+    Right,
+}
+#[derive(Debug, Clone, Copy)]
+pub enum Heave {
+    Up,
+    Down,
+}
+#[derive(Debug, Clone, Copy)]
+pub enum Pace {
+    Sprint,
+    Slow,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RotationalMovement {
+    pub yaw: f64,
+    pub pitch: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use math::{matrix::Matrix, vector::Vector};
+
+    use crate::transform::DepthOrder;
+
+    use super::*;
+
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    /// a second of settling look input, a second of holding W, then a second of coasting
+    fn simulate(hz: u32) -> Camera {
+        let mut camera = Camera::new();
+        camera.set_look_smoothing(Some(0.05));
+        camera.set_inertia(Some(Inertia {
+            acceleration: 8.,
+            damping: 4.,
+        }));
+        camera.set_invert_y(true);
+        camera.set_sensitivity_curve(SensitivityCurve::Power(1.5));
+        camera.rotate(RotationalMovement { yaw: 4., pitch: 2. });
+        let elapsed = 1. / hz as f64;
+        for frame in 0..hz * 3 {
+            let surge = (hz..hz * 2).contains(&frame).then_some(Surge::Forward);
+            let movement = TranslationalMovement {
+                surge,
+                sway: None,
+                heave: None,
+                pace: Some(Pace::Sprint),
+            };
+            camera.advance(elapsed);
+            camera.translate(movement, elapsed);
+        }
+        camera
+    }
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        let mut d = a;
+        d.sub(&b);
+        d.iter().map(|x| x * x).sum::<f64>().sqrt()
+    }
+
+    #[test]
+    fn test_frame_rate_independence() {
+        let slow = simulate(30);
+        let fast = simulate(144);
+        assert!((slow.yaw() - fast.yaw()).abs() < 1e-6);
+        assert!((slow.pitch() - fast.pitch()).abs() < 1e-6);
+        assert!(distance(slow.position(), fast.position()) < 1e-6);
+        assert!(distance(slow.velocity(), fast.velocity()) < 1e-6);
+
+        // inverted look down
+        assert!(slow.pitch() < 0.);
+        // still coasting, but nearly stopped
+        let speed = distance(slow.velocity(), [0.; 3]);
+        assert!(0. < speed && speed < 0.2);
+    }
+
+    #[test]
+    fn test_zero_rates() {
+        let mut camera = Camera::new();
+        camera.set_look_smoothing(Some(0.));
+        assert_eq!(camera.look_smoothing, None);
+        camera.rotate(RotationalMovement { yaw: 1., pitch: 0. });
+        camera.advance(0.1);
+        assert!(camera.yaw().is_finite() && camera.yaw() != Camera::new().yaw());
+
+        camera.set_inertia(Some(Inertia {
+            acceleration: 0.,
+            damping: 0.,
+        }));
+        let forward = TranslationalMovement {
+            surge: Some(Surge::Forward),
+            sway: None,
+            heave: None,
+            pace: None,
+        };
+        camera.translate(forward, 0.5);
+        // never accelerates from rest, and never slows down either
+        assert_close(camera.position(), [0.; 3]);
+        assert_close(camera.velocity(), [0.; 3]);
+        camera.velocity = [1., 0., 0.];
+        let still = TranslationalMovement {
+            surge: None,
+            ..forward
+        };
+        camera.translate(still, 0.5);
+        assert_close(camera.position(), [0.5, 0., 0.]);
+        assert_close(camera.velocity(), [1., 0., 0.]);
+    }
+
+    #[test]
+    fn test_quaternion_mode() {
+        let mut camera = Camera::new();
+        camera.set_yaw(0.3);
+        camera.set_pitch(-0.4);
+        let view = camera.view_matrix();
+        let before = camera.facing();
+        camera.set_orientation_mode(OrientationMode::Quaternion);
+        assert_close(camera.facing(), before);
+        assert!(camera.view_matrix().closes_to(&view));
+
+        // over the top without locking
+        for _ in 0..30 {
+            camera.rotate(RotationalMovement { yaw: 0., pitch: 1. });
+        }
+        let over = camera.facing();
+        assert_close(over, facing(0.3, 2.6));
+        assert!(camera.up()[1] < 0.);
+
+        camera.roll(PI / 2.);
+        assert_close(camera.facing(), over);
+        let mut right = over.cross(&camera.up());
+        right.normalize();
+        assert_close(camera.right(), right);
+
+        camera.set_orientation_mode(OrientationMode::YawPitch);
+        assert_close(camera.facing(), over);
+        assert_close(camera.up(), {
+            let mut up = camera.facing().cross(&[0., 1., 0.]).cross(&camera.facing());
+            up.normalize();
+            up
+        });
+    }
+
+    #[test]
+    fn test_screen_ray() {
+        let wnd_size = WndSize {
+            width: 200,
+            height: 100,
+        };
+        let mut camera = Camera::new();
+        camera.set_position([1., 2., 3.]);
+        let mut projection = Projection::perspective(PI / 2., 0.5, None);
+        projection.set_depth(DepthOrder::Reversed);
+        camera.set_projection(projection);
+        let center = Position2D { x: 100., y: 50. };
+        let ray = camera.screen_ray(center, wnd_size).unwrap();
+        assert_close(ray.origin, [1., 2., 2.5]);
+        assert_close(ray.direction, [0., 0., -1.]);
+
+        // top edge at 45° above the facing
+        let top = Position2D { x: 100., y: 0. };
+        let ray = camera.screen_ray(top, wnd_size).unwrap();
+        let half = 0.5_f64.sqrt();
+        assert_close(ray.direction, [0., half, -half]);
+
+        camera.set_projection(Projection::orthographic(2., 0.5, 10.));
+        let corner = Position2D { x: 0., y: 100. };
+        let ray = camera.screen_ray(corner, wnd_size).unwrap();
+        assert_close(ray.origin, [-1., 1., 2.5]);
+        assert_close(ray.direction, [0., 0., -1.]);
+    }
+}
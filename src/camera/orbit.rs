@@ -0,0 +1,186 @@
+use std::f64::consts::PI;
+
+use math::vector::{Vector, VectorMut};
+
+use crate::{
+    input::Position2D,
+    transform::{look_at, TransformMatrix},
+};
+
+use super::{
+    bounding_sphere, facing,
+    projection::{Projection, ViewVolume},
+    CameraController, Heave, RotationalMovement, Surge, Sway, TranslationalMovement,
+    NEAR_PERPENDICULAR, TRI_PERIOD,
+};
+
+const MIN_DISTANCE: f64 = 0.01;
+const MAX_DISTANCE: f64 = 1_000.;
+/// wheel offset to the exponent of the distance scale
+const DOLLY_RATE: f64 = 16.;
+
+/// Circles around a target point; the eye sits `distance` behind the target along the facing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCamera {
+    target: [f64; 3],
+    distance: f64,
+    speed: f64,
+    sensitivity: f64,
+    pan_sensitivity: f64,
+    yaw: f64,
+    pitch: f64,
+    projection: Projection,
+}
+impl OrbitCamera {
+    pub fn new() -> Self {
+        Self {
+            target: [0., 0., 0.],
+            distance: 5.,
+            speed: 2.5,
+            sensitivity: 0.1,
+            pan_sensitivity: 0.002,
+            yaw: -PI / 2.,
+            pitch: 0.,
+            projection: Projection::default(),
+        }
+    }
+    pub fn set_target(&mut self, v: [f64; 3]) {
+        self.target = v;
+    }
+    pub fn target(&self) -> [f64; 3] {
+        self.target
+    }
+    pub fn set_distance(&mut self, v: f64) {
+        self.distance = v.clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+    pub fn set_yaw(&mut self, yaw: f64) {
+        self.yaw = yaw % TRI_PERIOD;
+    }
+    pub fn set_pitch(&mut self, pitch: f64) {
+        self.pitch = (pitch % TRI_PERIOD).clamp(-NEAR_PERPENDICULAR, NEAR_PERPENDICULAR);
+    }
+    /// from the eye towards the target
+    pub fn facing(&self) -> [f64; 3] {
+        facing(self.yaw, self.pitch)
+    }
+    pub fn eye(&self) -> [f64; 3] {
+        let mut back = self.facing();
+        back.mul(-self.distance);
+        let mut eye = self.target;
+        eye.add(&back);
+        eye
+    }
+
+    /// screen right and screen up in world space
+    fn screen_axes(&self) -> ([f64; 3], [f64; 3]) {
+        let facing = self.facing();
+        let mut right = facing.cross(&[0., 1., 0.]);
+        right.normalize();
+        let up = right.cross(&facing);
+        (right, up)
+    }
+    fn move_target(&mut self, right: f64, up: f64) {
+        let (mut right_axis, mut up_axis) = self.screen_axes();
+        right_axis.mul(right);
+        up_axis.mul(up);
+        self.target.add(&right_axis);
+        self.target.add(&up_axis);
+    }
+}
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl CameraController for OrbitCamera {
+    fn view_matrix(&self) -> TransformMatrix {
+        look_at(self.eye(), self.target, [0., 1., 0.])
+    }
+    fn position(&self) -> [f64; 3] {
+        self.eye()
+    }
+    fn projection(&self) -> Projection {
+        self.projection
+    }
+    fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+    fn rotate(&mut self, movement: RotationalMovement) {
+        self.set_pitch(self.pitch + movement.pitch * self.sensitivity);
+        self.set_yaw(self.yaw + movement.yaw * self.sensitivity);
+    }
+    fn translate(&mut self, movement: TranslationalMovement, elapsed: f64) {
+        let dist = self.speed * movement.pace_factor() * elapsed;
+        match movement.surge {
+            None => (),
+            Some(Surge::Forward) => self.set_distance(self.distance - dist),
+            Some(Surge::Backward) => self.set_distance(self.distance + dist),
+        }
+        let right = match movement.sway {
+            None => 0.,
+            Some(Sway::Left) => -dist,
+            Some(Sway::Right) => dist,
+        };
+        let up = match movement.heave {
+            None => 0.,
+            Some(Heave::Down) => -dist,
+            Some(Heave::Up) => dist,
+        };
+        self.move_target(right, up);
+    }
+    /// dollies in perspective since narrowing the fov would distort the view
+    fn zoom(&mut self, offset: f64) {
+        match self.projection.volume() {
+            ViewVolume::Perspective { .. } => {
+                self.set_distance(self.distance * (-offset * DOLLY_RATE).exp());
+            }
+            ViewVolume::Orthographic { .. } => self.projection.zoom(offset),
+        }
+    }
+    /// drags the scene along with the cursor
+    fn pan(&mut self, offset: Position2D) {
+        let scale = self.distance * self.pan_sensitivity;
+        self.move_target(-offset.x * scale, offset.y * scale);
+    }
+    fn frame(&mut self, min: [f64; 3], max: [f64; 3], aspect: f64) {
+        let (center, radius) = bounding_sphere(min, max);
+        self.target = center;
+        let distance = self.projection.frame(radius, aspect);
+        self.set_distance(distance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dist(a: [f64; 3], b: [f64; 3]) -> f64 {
+        let mut d = a;
+        d.sub(&b);
+        d.iter().map(|x| x * x).sum::<f64>().sqrt()
+    }
+
+    #[test]
+    fn test_orbit() {
+        let mut camera = OrbitCamera::new();
+        camera.frame([-1., -1., -1.], [3., 1., 1.], 1.);
+        assert_eq!(camera.target(), [1., 0., 0.]);
+        let distance = camera.distance();
+        assert!((dist(camera.eye(), camera.target()) - distance).abs() < 1e-9);
+
+        camera.rotate(RotationalMovement { yaw: 7., pitch: 3. });
+        assert!((dist(camera.eye(), camera.target()) - distance).abs() < 1e-9);
+
+        camera.zoom(0.1);
+        assert!(camera.distance() < distance);
+
+        let eye = camera.eye();
+        camera.pan(Position2D { x: 10., y: -4. });
+        let moved = dist(camera.eye(), eye);
+        assert!(0. < moved);
+        assert!((dist(camera.target(), [1., 0., 0.]) - moved).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    time::Instant,
+};
+
+use math::vector::VectorMut;
+use serde::{Deserialize, Serialize};
+
+use super::facing;
+
+/// everything needed to put a [`super::Camera`] back where it was
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraPose {
+    pub position: [f64; 3],
+    pub yaw: f64,
+    pub pitch: f64,
+    /// `None` for an orthographic projection
+    pub fov: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// seconds since the start of the path
+    pub time: f64,
+    pub pose: CameraPose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Spline {
+    /// passes through every keyframe position
+    CatmullRom,
+    /// Cubic Bezier segments through every keyframe position.
+    ///
+    /// Each keyframe's handles lie along the line between its neighbours, a third of the segment
+    /// long, which keeps unevenly spaced keyframes from overshooting.
+    Bezier,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraPath {
+    spline: Spline,
+    keyframes: Vec<Keyframe>,
+}
+impl CameraPath {
+    pub fn new(spline: Spline) -> Self {
+        Self {
+            spline,
+            keyframes: vec![],
+        }
+    }
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn set_spline(&mut self, v: Spline) {
+        self.spline = v;
+    }
+    pub fn spline(&self) -> Spline {
+        self.spline
+    }
+    /// keeps keyframes ordered by time
+    pub fn insert(&mut self, keyframe: Keyframe) {
+        let i = self.keyframes.partition_point(|k| k.time <= keyframe.time);
+        self.keyframes.insert(i, keyframe);
+    }
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+    pub fn duration(&self) -> f64 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.,
+        }
+    }
+
+    /// pose at `time` seconds, clamped to the ends of the path
+    pub fn sample(&self, time: f64) -> Option<CameraPose> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        let time = time.clamp(first.time, last.time);
+        let i = self
+            .keyframes
+            .partition_point(|k| k.time <= time)
+            .clamp(1, self.keyframes.len().max(2) - 1)
+            - 1;
+        let a = &self.keyframes[i];
+        let Some(b) = self.keyframes.get(i + 1) else {
+            return Some(a.pose);
+        };
+        let span = b.time - a.time;
+        let u = if span <= 0. {
+            1.
+        } else {
+            (time - a.time) / span
+        };
+
+        let position = match self.spline {
+            Spline::CatmullRom => {
+                let before = &self.keyframes[i.saturating_sub(1)];
+                let after = self.keyframes.get(i + 2).unwrap_or(b);
+                catmull_rom(
+                    before.pose.position,
+                    a.pose.position,
+                    b.pose.position,
+                    after.pose.position,
+                    u,
+                )
+            }
+            Spline::Bezier => {
+                let (start, end) = (a.pose.position, b.pose.position);
+                let mut chord = end;
+                chord.sub(&start);
+                let reach = length(chord) / 3.;
+                let mut out_handle = self.handle_direction(i);
+                out_handle.mul(reach);
+                out_handle.add(&start);
+                let mut in_handle = self.handle_direction(i + 1);
+                in_handle.mul(-reach);
+                in_handle.add(&end);
+                bezier(vec![start, out_handle, in_handle, end], u)
+            }
+        };
+        let (yaw, pitch) = slerp_orientation(&a.pose, &b.pose, u);
+        let fov = match (a.pose.fov, b.pose.fov) {
+            (Some(a), Some(b)) => Some(lerp(a, b, u)),
+            (a, b) => {
+                if u < 0.5 {
+                    a
+                } else {
+                    b
+                }
+            }
+        };
+        Some(CameraPose {
+            position,
+            yaw,
+            pitch,
+            fov,
+        })
+    }
+    /// unit direction of the [`Spline::Bezier`] handles at keyframe `i`
+    fn handle_direction(&self, i: usize) -> [f64; 3] {
+        let before = self.keyframes[i.saturating_sub(1)].pose.position;
+        let after = self.keyframes.get(i + 1).unwrap_or(&self.keyframes[i]);
+        let mut direction = after.pose.position;
+        direction.sub(&before);
+        let len = length(direction);
+        if len <= f64::EPSILON {
+            return [0.; 3];
+        }
+        direction.mul(1. / len);
+        direction
+    }
+}
+
+/// plays a [`CameraPath`] against the frame clock
+#[derive(Debug, Clone)]
+pub struct PathPlayback {
+    path: CameraPath,
+    speed: f64,
+    start: Option<Instant>,
+}
+impl PathPlayback {
+    /// `speed` scales path time; 2 plays twice as fast
+    pub fn new(path: CameraPath, speed: f64) -> Self {
+        Self {
+            path,
+            speed,
+            start: None,
+        }
+    }
+    pub fn path(&self) -> &CameraPath {
+        &self.path
+    }
+    /// path time at `now`; the first call starts the clock
+    pub fn elapsed(&mut self, now: Instant) -> f64 {
+        let start = *self.start.get_or_insert(now);
+        now.saturating_duration_since(start).as_secs_f64() * self.speed
+    }
+    pub fn pose(&mut self, now: Instant) -> Option<CameraPose> {
+        let first = self.path.keyframes().first()?.time;
+        let elapsed = self.elapsed(now);
+        self.path.sample(first + elapsed)
+    }
+    pub fn is_finished(&mut self, now: Instant) -> bool {
+        self.path.duration() <= self.elapsed(now)
+    }
+}
+
+fn lerp(a: f64, b: f64, u: f64) -> f64 {
+    a + (b - a) * u
+}
+fn length(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+fn lerp_points(a: [f64; 3], b: [f64; 3], u: f64) -> [f64; 3] {
+    [
+        lerp(a[0], b[0], u),
+        lerp(a[1], b[1], u),
+        lerp(a[2], b[2], u),
+    ]
+}
+/// uniform Catmull-Rom between `p1` and `p2`
+fn catmull_rom(p0: [f64; 3], p1: [f64; 3], p2: [f64; 3], p3: [f64; 3], u: f64) -> [f64; 3] {
+    let (u2, u3) = (u * u, u * u * u);
+    let mut out = [0.; 3];
+    for i in 0..3 {
+        out[i] = 0.5
+            * (2. * p1[i]
+                + (-p0[i] + p2[i]) * u
+                + (2. * p0[i] - 5. * p1[i] + 4. * p2[i] - p3[i]) * u2
+                + (-p0[i] + 3. * p1[i] - 3. * p2[i] + p3[i]) * u3);
+    }
+    out
+}
+/// de Casteljau
+fn bezier(mut points: Vec<[f64; 3]>, u: f64) -> [f64; 3] {
+    while 1 < points.len() {
+        points = points
+            .windows(2)
+            .map(|w| lerp_points(w[0], w[1], u))
+            .collect();
+    }
+    points[0]
+}
+/// turns along the shortest arc between the two facings
+fn slerp_orientation(a: &CameraPose, b: &CameraPose, u: f64) -> (f64, f64) {
+    let from = facing(a.yaw, a.pitch);
+    let to = facing(b.yaw, b.pitch);
+    let cos = (from[0] * to[0] + from[1] * to[1] + from[2] * to[2]).clamp(-1., 1.);
+    let angle = cos.acos();
+    let sin = angle.sin();
+    if sin < 1e-6 {
+        // opposite facings have no single shortest arc; turn about the vertical instead
+        let mut yaw_change = (b.yaw - a.yaw) % super::TRI_PERIOD;
+        if std::f64::consts::PI < yaw_change.abs() {
+            yaw_change -= super::TRI_PERIOD.copysign(yaw_change);
+        }
+        return (a.yaw + yaw_change * u, lerp(a.pitch, b.pitch, u));
+    }
+    let mut start = from;
+    start.mul(((1. - u) * angle).sin() / sin);
+    let mut end = to;
+    end.mul((u * angle).sin() / sin);
+    start.add(&end);
+    (start[2].atan2(start[0]), start[1].clamp(-1., 1.).asin())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, time::Duration};
+
+    use super::*;
+
+    fn keyframe(time: f64, position: [f64; 3], yaw: f64) -> Keyframe {
+        Keyframe {
+            time,
+            pose: CameraPose {
+                position,
+                yaw,
+                pitch: 0.,
+                fov: Some(1.),
+            },
+        }
+    }
+    fn path(spline: Spline) -> CameraPath {
+        let mut path = CameraPath::new(spline);
+        path.insert(keyframe(2., [2., 0., 0.], 0.));
+        path.insert(keyframe(0., [0., 0., 0.], 0.));
+        path.insert(keyframe(1., [1., 1., 0.], 0.));
+        path.insert(keyframe(3., [3., 0., 0.], 0.));
+        path
+    }
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_splines() {
+        let path = path(Spline::CatmullRom);
+        assert_eq!(path.duration(), 3.);
+        for k in path.keyframes() {
+            assert_close(path.sample(k.time).unwrap().position, k.pose.position);
+        }
+        assert_close(path.sample(-1.).unwrap().position, [0., 0., 0.]);
+        assert_close(path.sample(9.).unwrap().position, [3., 0., 0.]);
+
+        let mut path = path;
+        path.set_spline(Spline::Bezier);
+        for k in path.keyframes() {
+            assert_close(path.sample(k.time).unwrap().position, k.pose.position);
+        }
+        // the segments stay below the peak at 1 rather than overshooting it
+        let y = path.sample(1.5).unwrap().position[1];
+        assert!(0. < y && y < 1.);
+        // straight runs stay straight
+        let mut line = CameraPath::new(Spline::Bezier);
+        line.insert(keyframe(0., [0., 0., 0.], 0.));
+        line.insert(keyframe(1., [1., 0., 0.], 0.));
+        line.insert(keyframe(4., [5., 0., 0.], 0.));
+        let position = line.sample(2.5).unwrap().position;
+        assert!(1. < position[0] && position[0] < 5.);
+        assert_close([position[1], position[2], 0.], [0.; 3]);
+    }
+
+    #[test]
+    fn test_bezier_segments_share_time() {
+        let mut path = CameraPath::new(Spline::Bezier);
+        for (i, yaw) in [0., 0.5, 1., 1.5, 2.].into_iter().enumerate() {
+            let t = i as f64;
+            path.insert(keyframe(t, [t, (t * 2.).sin(), 0.], yaw));
+        }
+        // orientation and position both reach each keyframe at its time
+        for k in path.keyframes() {
+            let pose = path.sample(k.time).unwrap();
+            assert_close(pose.position, k.pose.position);
+            assert!((pose.yaw - k.pose.yaw).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_shortest_arc() {
+        let mut path = CameraPath::new(Spline::CatmullRom);
+        path.insert(keyframe(0., [0.; 3], PI - 0.2));
+        path.insert(keyframe(1., [0.; 3], -PI + 0.2));
+        let pose = path.sample(0.5).unwrap();
+        // through the back rather than all the way round through 0
+        assert!((pose.yaw.abs() - PI).abs() < 1e-9);
+        assert!(pose.pitch.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_playback() {
+        let path = path(Spline::CatmullRom);
+        let mut playback = PathPlayback::new(path.clone(), 2.);
+        let start = Instant::now();
+        assert_eq!(playback.pose(start), path.sample(0.));
+        let now = start + Duration::from_millis(500);
+        assert_eq!(playback.pose(now), path.sample(1.));
+        assert!(!playback.is_finished(now));
+        assert!(playback.is_finished(start + Duration::from_millis(1500)));
+
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(serde_json::from_str::<CameraPath>(&json).unwrap(), path);
+    }
+}
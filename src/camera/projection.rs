@@ -0,0 +1,213 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::transform::{
+    orthographic, perspective, perspective_infinite, reverse_depth, ClipSpace, DepthOrder,
+    DepthRange, TransformMatrix,
+};
+
+const MIN_FOV: f64 = 0.001;
+const MAX_FOV: f64 = PI / 4.;
+const MIN_HEIGHT: f64 = 0.001;
+/// zoom offset to the exponent of the orthographic height scale
+const ORTHOGRAPHIC_ZOOM_RATE: f64 = 4.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ViewVolume {
+    Perspective {
+        fov: f64,
+        near: f64,
+        /// `None` for an infinite far plane
+        far: Option<f64>,
+    },
+    Orthographic {
+        height: f64,
+        near: f64,
+        far: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Projection {
+    volume: ViewVolume,
+    depth: DepthOrder,
+    clip: ClipSpace,
+}
+impl Projection {
+    pub fn perspective(fov: f64, near: f64, far: Option<f64>) -> Self {
+        Self {
+            volume: ViewVolume::Perspective { fov, near, far },
+            depth: DepthOrder::Standard,
+            clip: ClipSpace::WGPU,
+        }
+    }
+    pub fn orthographic(height: f64, near: f64, far: f64) -> Self {
+        Self {
+            volume: ViewVolume::Orthographic { height, near, far },
+            depth: DepthOrder::Standard,
+            clip: ClipSpace::WGPU,
+        }
+    }
+
+    pub fn set_volume(&mut self, v: ViewVolume) {
+        self.volume = v;
+    }
+    pub fn volume(&self) -> ViewVolume {
+        self.volume
+    }
+    pub fn set_depth(&mut self, v: DepthOrder) {
+        self.depth = v;
+    }
+    pub fn depth(&self) -> DepthOrder {
+        self.depth
+    }
+    pub fn set_clip(&mut self, v: ClipSpace) {
+        self.clip = v;
+    }
+    pub fn clip(&self) -> ClipSpace {
+        self.clip
+    }
+    /// NDC depth of the near plane
+    pub fn near_depth(&self) -> f64 {
+        match (self.depth, self.clip.depth) {
+            (DepthOrder::Reversed, _) => 1.,
+            (DepthOrder::Standard, DepthRange::ZeroToOne) => 0.,
+            (DepthOrder::Standard, DepthRange::NegOneToOne) => -1.,
+        }
+    }
+    /// NDC depth between the near and far planes, finite even if the far plane is infinite
+    pub fn mid_depth(&self) -> f64 {
+        match self.clip.depth {
+            DepthRange::NegOneToOne => 0.,
+            DepthRange::ZeroToOne => 0.5,
+        }
+    }
+    /// ignored by orthographic projections
+    pub fn set_fov(&mut self, v: f64) {
+        if let ViewVolume::Perspective { fov, .. } = &mut self.volume {
+            *fov = v.clamp(MIN_FOV, MAX_FOV);
+        }
+    }
+    pub fn fov(&self) -> Option<f64> {
+        match self.volume {
+            ViewVolume::Perspective { fov, .. } => Some(fov),
+            ViewVolume::Orthographic { .. } => None,
+        }
+    }
+
+    /// positive narrows the view
+    pub fn zoom(&mut self, offset: f64) {
+        match &mut self.volume {
+            ViewVolume::Perspective { fov, .. } => {
+                *fov = (*fov - offset).clamp(MIN_FOV, MAX_FOV);
+            }
+            ViewVolume::Orthographic { height, .. } => {
+                *height = (*height * (-offset * ORTHOGRAPHIC_ZOOM_RATE).exp()).max(MIN_HEIGHT);
+            }
+        }
+    }
+    /// Fits a sphere of `radius` into a view `aspect` times as wide as it is tall and returns
+    /// how far away its center should be.
+    pub fn frame(&mut self, radius: f64, aspect: f64) -> f64 {
+        match &mut self.volume {
+            ViewVolume::Perspective { fov, .. } => {
+                let horizontal = ((*fov / 2.).tan() * aspect).atan();
+                radius / horizontal.min(*fov / 2.).sin()
+            }
+            ViewVolume::Orthographic { height, near, .. } => {
+                *height = radius * 2. / aspect.min(1.);
+                *near + radius * 2.
+            }
+        }
+    }
+
+    pub fn matrix(&self, aspect: f64) -> TransformMatrix {
+        let m = match self.volume {
+            ViewVolume::Perspective {
+                fov,
+                near,
+                far: Some(far),
+            } => perspective(fov, aspect, near, far, self.clip),
+            ViewVolume::Perspective {
+                fov,
+                near,
+                far: None,
+            } => perspective_infinite(fov, aspect, near, self.clip),
+            ViewVolume::Orthographic { height, near, far } => {
+                let top = height / 2.;
+                let right = top * aspect;
+                orthographic(-right, right, top, -top, near, far, self.clip)
+            }
+        };
+        match self.depth {
+            DepthOrder::Standard => m,
+            DepthOrder::Reversed => reverse_depth(self.clip.depth).mul_matrix_square(&m),
+        }
+    }
+}
+impl Default for Projection {
+    fn default() -> Self {
+        Self::perspective(MAX_FOV, 0.1, Some(100.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math::{matrix::Matrix, vector::Vector};
+
+    use crate::transform::{point, Point, PointMatrix};
+
+    use super::*;
+
+    fn depth(projection: &Projection, z: f64) -> f64 {
+        let m = projection.matrix(1.);
+        let mut p: PointMatrix = point([0., 0., z]).into_matrix().unwrap();
+        m.mul_matrix_in(&p.clone(), &mut p);
+        let p = Point::try_from(p).unwrap();
+        p[2] / p[3]
+    }
+
+    #[test]
+    fn test_reversed_infinite() {
+        let mut projection = Projection::perspective(PI / 4., 0.1, None);
+        projection.set_depth(DepthOrder::Reversed);
+        assert!((depth(&projection, -0.1) - 1.).abs() < 1e-9);
+        assert!(depth(&projection, -1e9).abs() < 1e-9);
+        assert!(depth(&projection, -10.) < depth(&projection, -1.));
+    }
+
+    #[test]
+    fn test_frame_portrait() {
+        let (min, max) = ([-2., -1., -1.], [2., 1., 1.]);
+        let radius = 6_f64.sqrt();
+        let aspect = 0.5;
+        for mut projection in [
+            Projection::perspective(PI / 4., 0.1, None),
+            Projection::orthographic(2., 0.1, 100.),
+        ] {
+            let distance = projection.frame(radius, aspect);
+            let m = projection.matrix(aspect);
+            for i in 0..8 {
+                let corner = [0, 1, 2].map(|axis| match i >> axis & 1 {
+                    0 => min[axis],
+                    _ => max[axis],
+                });
+                let z = corner[2] - distance;
+                let mut p: PointMatrix = point([corner[0], corner[1], z]).into_matrix().unwrap();
+                m.mul_matrix_in(&p.clone(), &mut p);
+                let p = Point::try_from(p).unwrap();
+                assert!(0. < p[3]);
+                assert!((p[0] / p[3]).abs() <= 1., "{projection:?} clips {corner:?}");
+                assert!((p[1] / p[3]).abs() <= 1., "{projection:?} clips {corner:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_orthographic() {
+        let projection = Projection::orthographic(2., 1., 3.);
+        assert!(depth(&projection, -1.).abs() < 1e-9);
+        assert!((depth(&projection, -3.) - 1.).abs() < 1e-9);
+    }
+}
@@ -18,11 +18,17 @@ async fn test_adapter() {
     let adapter = adapter(&instance, None).await.unwrap();
     println!("{:?}", adapter.get_info());
 }
+/// requested whenever the adapter has them
+const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_COMPRESSION_BC
+    .union(wgpu::Features::TEXTURE_COMPRESSION_ETC2)
+    .union(wgpu::Features::TEXTURE_COMPRESSION_ASTC)
+    .union(wgpu::Features::FLOAT32_FILTERABLE);
 pub async fn device(adapter: &wgpu::Adapter) -> anyhow::Result<(wgpu::Device, wgpu::Queue)> {
     let trace_path = None;
+    let optional_features = adapter.features() & OPTIONAL_FEATURES;
     let desc = wgpu::DeviceDescriptor {
         label: None,
-        required_features: wgpu::Features::empty() | wgpu::Features::POLYGON_MODE_LINE,
+        required_features: wgpu::Features::POLYGON_MODE_LINE | optional_features,
         required_limits: wgpu::Limits::downlevel_defaults(),
         memory_hints: wgpu::MemoryHints::Performance,
     };
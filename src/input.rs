@@ -1,17 +1,75 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct InputState {
     pressed: HashSet<winit::keyboard::KeyCode>,
+    pressed_buttons: HashSet<winit::event::MouseButton>,
     cursor_pos: Option<Position2D>,
     cursor_change: Option<Position2D>,
+    mouse_motion: Option<Position2D>,
+    is_cursor_captured: bool,
+    touches: HashMap<u64, Position2D>,
+    pinch: Option<f64>,
+    pan: Option<Position2D>,
 }
 impl InputState {
     pub fn new() -> Self {
         Self {
             pressed: HashSet::new(),
+            pressed_buttons: HashSet::new(),
             cursor_pos: None,
             cursor_change: None,
+            mouse_motion: None,
+            is_cursor_captured: false,
+            touches: HashMap::new(),
+            pinch: None,
+            pan: None,
+        }
+    }
+
+    /// feeds the parts of a window event that input state tracks
+    pub fn update_window_event(&mut self, event: &winit::event::WindowEvent) {
+        match event {
+            winit::event::WindowEvent::KeyboardInput {
+                device_id: _,
+                event,
+                is_synthetic: _,
+            } => self.update_key(event),
+            winit::event::WindowEvent::MouseInput {
+                device_id: _,
+                state,
+                button,
+            } => self.update_mouse_button(*button, *state),
+            winit::event::WindowEvent::CursorMoved {
+                device_id: _,
+                position,
+            } => self.update_cursor(Position2D {
+                x: position.x,
+                y: position.y,
+            }),
+            winit::event::WindowEvent::Touch(touch) => {
+                let location = Position2D {
+                    x: touch.location.x,
+                    y: touch.location.y,
+                };
+                self.update_touch(touch.id, touch.phase, location);
+            }
+            winit::event::WindowEvent::PinchGesture {
+                device_id: _,
+                delta,
+                phase: _,
+            } => self.update_pinch(*delta),
+            winit::event::WindowEvent::PanGesture {
+                device_id: _,
+                delta,
+                phase: _,
+            } => self.update_pan(Position2D {
+                x: delta.x as f64,
+                y: delta.y as f64,
+            }),
+            _ => (),
         }
     }
 
@@ -19,7 +77,14 @@ impl InputState {
         let winit::keyboard::PhysicalKey::Code(key) = event.physical_key else {
             return;
         };
-        match event.state {
+        self.update_key_code(key, event.state);
+    }
+    pub fn update_key_code(
+        &mut self,
+        key: winit::keyboard::KeyCode,
+        state: winit::event::ElementState,
+    ) {
+        match state {
             winit::event::ElementState::Pressed => {
                 self.pressed.insert(key);
             }
@@ -32,6 +97,24 @@ impl InputState {
         self.pressed.contains(&key)
     }
 
+    pub fn update_mouse_button(
+        &mut self,
+        button: winit::event::MouseButton,
+        state: winit::event::ElementState,
+    ) {
+        match state {
+            winit::event::ElementState::Pressed => {
+                self.pressed_buttons.insert(button);
+            }
+            winit::event::ElementState::Released => {
+                self.pressed_buttons.remove(&button);
+            }
+        }
+    }
+    pub fn is_mouse_pressed(&self, button: winit::event::MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
     pub fn update_cursor(&mut self, position: Position2D) {
         let prev = self.cursor_pos;
         self.cursor_pos = Some(position);
@@ -44,9 +127,95 @@ impl InputState {
         };
         self.cursor_change = Some(change);
     }
+    pub fn cursor_pos(&self) -> Option<Position2D> {
+        self.cursor_pos
+    }
     pub fn cursor_change(&self) -> Option<Position2D> {
         self.cursor_change
     }
+
+    /// raw device delta, free of screen edges and cursor acceleration
+    pub fn update_mouse_motion(&mut self, delta: Position2D) {
+        self.mouse_motion = Some(delta);
+    }
+    pub fn mouse_motion(&self) -> Option<Position2D> {
+        self.mouse_motion
+    }
+
+    pub fn set_cursor_captured(&mut self, captured: bool) {
+        self.is_cursor_captured = captured;
+    }
+    pub fn is_cursor_captured(&self) -> bool {
+        self.is_cursor_captured
+    }
+
+    /// two moving fingers on a touch screen count as a pinch and a pan
+    pub fn update_touch(&mut self, id: u64, phase: winit::event::TouchPhase, location: Position2D) {
+        self.pinch = None;
+        self.pan = None;
+        match phase {
+            winit::event::TouchPhase::Started => {
+                self.touches.insert(id, location);
+            }
+            winit::event::TouchPhase::Moved => {
+                let prev = self.two_finger_span();
+                if let Some(pos) = self.touches.get_mut(&id) {
+                    *pos = location;
+                }
+                let (Some(prev), Some(curr)) = (prev, self.two_finger_span()) else {
+                    return;
+                };
+                if 0. < prev.distance {
+                    self.pinch = Some(curr.distance / prev.distance - 1.);
+                }
+                self.pan = Some(Position2D {
+                    x: curr.center.x - prev.center.x,
+                    y: curr.center.y - prev.center.y,
+                });
+            }
+            winit::event::TouchPhase::Ended | winit::event::TouchPhase::Cancelled => {
+                self.touches.remove(&id);
+            }
+        }
+    }
+    pub fn touches(&self) -> impl Iterator<Item = (u64, Position2D)> + '_ {
+        self.touches.iter().map(|(id, pos)| (*id, *pos))
+    }
+    fn two_finger_span(&self) -> Option<TwoFingerSpan> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let mut touches = self.touches.values();
+        let a = touches.next()?;
+        let b = touches.next()?;
+        Some(TwoFingerSpan {
+            center: Position2D {
+                x: (a.x + b.x) / 2.,
+                y: (a.y + b.y) / 2.,
+            },
+            distance: (a.x - b.x).hypot(a.y - b.y),
+        })
+    }
+
+    /// trackpad pinch; positive magnifies
+    pub fn update_pinch(&mut self, delta: f64) {
+        self.pinch = Some(delta);
+        self.pan = None;
+    }
+    /// change in scale by the latest pinch; positive magnifies
+    pub fn pinch(&self) -> Option<f64> {
+        self.pinch
+    }
+
+    /// trackpad two-finger drag
+    pub fn update_pan(&mut self, delta: Position2D) {
+        self.pan = Some(delta);
+        self.pinch = None;
+    }
+    /// change in position by the latest two-finger drag
+    pub fn pan(&self) -> Option<Position2D> {
+        self.pan
+    }
 }
 impl Default for InputState {
     fn default() -> Self {
@@ -54,8 +223,40 @@ impl Default for InputState {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Position2D {
     pub x: f64,
     pub y: f64,
 }
+
+#[derive(Debug, Clone, Copy)]
+struct TwoFingerSpan {
+    center: Position2D,
+    distance: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::TouchPhase;
+
+    use super::*;
+
+    #[test]
+    fn test_two_finger_touch() {
+        let mut input = InputState::new();
+        input.update_touch(0, TouchPhase::Started, Position2D { x: 0., y: 0. });
+        input.update_touch(1, TouchPhase::Started, Position2D { x: 10., y: 0. });
+        assert_eq!(input.touches().count(), 2);
+        assert!(input.pinch().is_none());
+
+        input.update_touch(1, TouchPhase::Moved, Position2D { x: 20., y: 0. });
+        assert!((input.pinch().unwrap() - 1.).abs() < 1e-9);
+        assert_eq!(input.pan().unwrap(), Position2D { x: 5., y: 0. });
+
+        input.update_touch(1, TouchPhase::Ended, Position2D { x: 20., y: 0. });
+        assert!(input.pinch().is_none());
+        input.update_touch(0, TouchPhase::Moved, Position2D { x: 5., y: 5. });
+        assert!(input.pan().is_none());
+        assert_eq!(input.touches().count(), 1);
+    }
+}
@@ -1,10 +1,17 @@
+use std::time::{Instant, SystemTime};
+
 use input::InputState;
+use serde::{Deserialize, Serialize};
 
+pub mod bounds;
 pub mod camera;
 pub mod compute;
 pub mod delta_time;
 pub mod gpu;
 pub mod input;
+pub mod record;
+pub mod scene;
+pub mod skybox;
 pub mod texture;
 pub mod transform;
 pub mod triangle;
@@ -15,10 +22,11 @@ pub trait RenderApp: Draw + Resize + Update + core::fmt::Debug + Sync + Send {}
 #[derive(Debug)]
 pub struct RenderInitArgs<'a> {
     pub device: &'a wgpu::Device,
-    pub surface: &'a wgpu::Surface<'a>,
+    pub surface_format: wgpu::TextureFormat,
     pub adapter: &'a wgpu::Adapter,
     pub queue: &'a wgpu::Queue,
     pub wnd_size: WndSize,
+    pub time: FrameTime,
 }
 pub trait RenderInit: core::fmt::Debug {
     fn init(&self, args: RenderInitArgs<'_>) -> Box<dyn RenderApp>;
@@ -47,6 +55,7 @@ pub struct DrawArgs<'a> {
     pub device: &'a wgpu::Device,
     pub queue: &'a wgpu::Queue,
     pub context: &'a RenderContext,
+    pub time: FrameTime,
 }
 pub trait Draw {
     fn draw(&mut self, args: DrawArgs<'_>) -> RenderNextStep;
@@ -54,9 +63,20 @@ pub trait Draw {
 
 #[derive(Debug)]
 pub struct UpdateArgs<'a> {
-    pub event: winit::event::WindowEvent,
+    pub event: UpdateEvent,
     pub context: &'a RenderContext,
 }
+#[derive(Debug, Clone)]
+pub enum UpdateEvent {
+    Window(winit::event::WindowEvent),
+    Device(winit::event::DeviceEvent),
+    /// keyboard input in a form that replays can reproduce
+    Key {
+        key: winit::keyboard::KeyCode,
+        state: winit::event::ElementState,
+        repeat: bool,
+    },
+}
 pub trait Update {
     fn update(&mut self, args: UpdateArgs) -> RenderNextStep;
 }
@@ -71,7 +91,7 @@ pub trait Resize {
     fn resize(&mut self, args: ResizeArgs<'_>) -> RenderNextStep;
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WndSize {
     pub width: u32,
     pub height: u32,
@@ -81,3 +101,18 @@ pub struct WndSize {
 pub struct RenderNextStep {
     pub should_request_redraw: bool,
 }
+
+/// clock readings for a frame; replaced by recorded values during replay
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTime {
+    pub instant: Instant,
+    pub system: SystemTime,
+}
+impl FrameTime {
+    pub fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            system: SystemTime::now(),
+        }
+    }
+}
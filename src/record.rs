@@ -0,0 +1,450 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use winit::{
+    event::{DeviceEvent, DeviceId, ElementState, MouseButton, MouseScrollDelta, TouchPhase},
+    keyboard::KeyCode,
+};
+
+use crate::{
+    gpu::{adapter, device, instance},
+    input::Position2D,
+    texture::RenderTarget,
+    DrawArgs, FrameTime, RenderApp, RenderContext, RenderInitArgs, ResizeArgs, Update, UpdateArgs,
+    UpdateEvent, WndSize,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordTime {
+    /// since the recorder started
+    pub elapsed: Duration,
+    /// since UNIX epoch
+    pub system: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub time: RecordTime,
+    pub event: RecordEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordEvent {
+    Init(WndSize),
+    Resize(WndSize),
+    Frame,
+    Key {
+        key: KeyCode,
+        state: ElementState,
+        repeat: bool,
+    },
+    CursorMoved(Position2D),
+    CursorCaptured(bool),
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    },
+    Focused(bool),
+    MouseMotion(Position2D),
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        location: Position2D,
+    },
+    PinchGesture {
+        delta: f64,
+        phase: TouchPhase,
+    },
+    PanGesture {
+        delta: Position2D,
+        phase: TouchPhase,
+    },
+}
+impl RecordEvent {
+    /// `None` for events that have no effect on replay
+    pub fn from_window(event: &winit::event::WindowEvent) -> Option<Self> {
+        Some(match event {
+            winit::event::WindowEvent::KeyboardInput {
+                device_id: _,
+                event,
+                is_synthetic: _,
+            } => {
+                let winit::keyboard::PhysicalKey::Code(key) = event.physical_key else {
+                    return None;
+                };
+                Self::Key {
+                    key,
+                    state: event.state,
+                    repeat: event.repeat,
+                }
+            }
+            winit::event::WindowEvent::CursorMoved {
+                device_id: _,
+                position,
+            } => Self::CursorMoved(Position2D {
+                x: position.x,
+                y: position.y,
+            }),
+            winit::event::WindowEvent::MouseInput {
+                device_id: _,
+                state,
+                button,
+            } => Self::MouseInput {
+                state: *state,
+                button: *button,
+            },
+            winit::event::WindowEvent::MouseWheel {
+                device_id: _,
+                delta,
+                phase,
+            } => Self::MouseWheel {
+                delta: *delta,
+                phase: *phase,
+            },
+            winit::event::WindowEvent::Focused(focused) => Self::Focused(*focused),
+            winit::event::WindowEvent::Touch(touch) => Self::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                location: Position2D {
+                    x: touch.location.x,
+                    y: touch.location.y,
+                },
+            },
+            winit::event::WindowEvent::PinchGesture {
+                device_id: _,
+                delta,
+                phase,
+            } => Self::PinchGesture {
+                delta: *delta,
+                phase: *phase,
+            },
+            winit::event::WindowEvent::PanGesture {
+                device_id: _,
+                delta,
+                phase,
+            } => Self::PanGesture {
+                delta: Position2D {
+                    x: delta.x as f64,
+                    y: delta.y as f64,
+                },
+                phase: *phase,
+            },
+            _ => return None,
+        })
+    }
+    pub fn from_device(event: &DeviceEvent) -> Option<Self> {
+        Some(match event {
+            DeviceEvent::MouseMotion { delta } => Self::MouseMotion(Position2D {
+                x: delta.0,
+                y: delta.1,
+            }),
+            _ => return None,
+        })
+    }
+}
+
+/// writes one JSON record per line so that a crashed run still leaves a usable file
+#[derive(Debug)]
+pub struct Recorder<W = BufWriter<File>> {
+    writer: W,
+    start: Instant,
+}
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+impl<W> Recorder<W>
+where
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn record(&mut self, time: FrameTime, event: RecordEvent) -> anyhow::Result<()> {
+        let is_frame = event == RecordEvent::Frame;
+        let time = RecordTime {
+            elapsed: time.instant.saturating_duration_since(self.start),
+            system: time.system.duration_since(UNIX_EPOCH)?,
+        };
+        let record = Record { time, event };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.writer.write_all(b"\n")?;
+        if is_frame {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ReplayStep {
+    Resize(WndSize),
+    Frame(FrameTime),
+}
+
+#[derive(Debug)]
+pub struct Replay {
+    start: Instant,
+    wnd_size: WndSize,
+    init_time: RecordTime,
+    records: std::vec::IntoIter<Record>,
+}
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+        Self::read(BufReader::new(file))
+    }
+    pub fn read(reader: impl BufRead) -> anyhow::Result<Self> {
+        let mut records = vec![];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).with_context(|| format!("line {}", i + 1))?;
+            records.push(record);
+        }
+        Self::new(records)
+    }
+    pub fn new(records: Vec<Record>) -> anyhow::Result<Self> {
+        let mut records = records.into_iter();
+        let Some(Record {
+            time,
+            event: RecordEvent::Init(wnd_size),
+        }) = records.next()
+        else {
+            anyhow::bail!("recording does not start with an init record");
+        };
+        Ok(Self {
+            start: Instant::now(),
+            wnd_size,
+            init_time: time,
+            records,
+        })
+    }
+
+    pub fn wnd_size(&self) -> WndSize {
+        self.wnd_size
+    }
+    pub fn init_time(&self) -> FrameTime {
+        self.frame_time(self.init_time)
+    }
+
+    /// feeds recorded input into `context` and `app` until the next resize or frame
+    pub fn step<A>(&mut self, app: &mut A, context: &mut RenderContext) -> Option<ReplayStep>
+    where
+        A: Update + ?Sized,
+    {
+        while let Some(record) = self.records.next() {
+            let event = match record.event {
+                RecordEvent::Init(_) => continue,
+                RecordEvent::Resize(size) => return Some(ReplayStep::Resize(size)),
+                RecordEvent::Frame => {
+                    return Some(ReplayStep::Frame(self.frame_time(record.time)));
+                }
+                RecordEvent::Key { key, state, repeat } => {
+                    context.input.update_key_code(key, state);
+                    UpdateEvent::Key { key, state, repeat }
+                }
+                RecordEvent::CursorCaptured(captured) => {
+                    context.input.set_cursor_captured(captured);
+                    continue;
+                }
+                RecordEvent::CursorMoved(pos) => {
+                    UpdateEvent::Window(winit::event::WindowEvent::CursorMoved {
+                        device_id: DeviceId::dummy(),
+                        position: winit::dpi::PhysicalPosition::new(pos.x, pos.y),
+                    })
+                }
+                RecordEvent::MouseInput { state, button } => {
+                    UpdateEvent::Window(winit::event::WindowEvent::MouseInput {
+                        device_id: DeviceId::dummy(),
+                        state,
+                        button,
+                    })
+                }
+                RecordEvent::MouseWheel { delta, phase } => {
+                    UpdateEvent::Window(winit::event::WindowEvent::MouseWheel {
+                        device_id: DeviceId::dummy(),
+                        delta,
+                        phase,
+                    })
+                }
+                RecordEvent::Focused(focused) => {
+                    UpdateEvent::Window(winit::event::WindowEvent::Focused(focused))
+                }
+                RecordEvent::Touch {
+                    id,
+                    phase,
+                    location,
+                } => UpdateEvent::Window(winit::event::WindowEvent::Touch(winit::event::Touch {
+                    device_id: DeviceId::dummy(),
+                    phase,
+                    location: winit::dpi::PhysicalPosition::new(location.x, location.y),
+                    force: None,
+                    id,
+                })),
+                RecordEvent::PinchGesture { delta, phase } => {
+                    UpdateEvent::Window(winit::event::WindowEvent::PinchGesture {
+                        device_id: DeviceId::dummy(),
+                        delta,
+                        phase,
+                    })
+                }
+                RecordEvent::PanGesture { delta, phase } => {
+                    UpdateEvent::Window(winit::event::WindowEvent::PanGesture {
+                        device_id: DeviceId::dummy(),
+                        delta: winit::dpi::PhysicalPosition::new(delta.x as f32, delta.y as f32),
+                        phase,
+                    })
+                }
+                RecordEvent::MouseMotion(delta) => {
+                    context.input.update_mouse_motion(delta);
+                    UpdateEvent::Device(DeviceEvent::MouseMotion {
+                        delta: (delta.x, delta.y),
+                    })
+                }
+            };
+            if let UpdateEvent::Window(event) = &event {
+                context.input.update_window_event(event);
+            }
+            let args = UpdateArgs { event, context };
+            app.update(args);
+        }
+        None
+    }
+
+    fn frame_time(&self, time: RecordTime) -> FrameTime {
+        FrameTime {
+            instant: self.start + time.elapsed,
+            system: UNIX_EPOCH + time.system,
+        }
+    }
+}
+
+/// Replays into an app rendering to an offscreen texture.
+pub async fn replay_headless<A, F>(mut replay: Replay, init: F) -> anyhow::Result<Box<A>>
+where
+    A: RenderApp + ?Sized,
+    F: FnOnce(RenderInitArgs<'_>) -> Box<A>,
+{
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let instance = instance();
+    let adapter = adapter(&instance, None).await.context("no adapter")?;
+    let (device, queue) = device(&adapter).await?;
+    let label = Some("replay target");
+    let mut target = RenderTarget::new(&device, replay.wnd_size(), &[FORMAT], None, label)?;
+    let args = RenderInitArgs {
+        device: &device,
+        surface_format: FORMAT,
+        adapter: &adapter,
+        queue: &queue,
+        wnd_size: replay.wnd_size(),
+        time: replay.init_time(),
+    };
+    let mut app = init(args);
+    let mut context = RenderContext::new();
+    while let Some(step) = replay.step(app.as_mut(), &mut context) {
+        match step {
+            ReplayStep::Resize(size) => {
+                target.resize(&device, size);
+                let args = ResizeArgs {
+                    device: &device,
+                    size,
+                    context: &context,
+                };
+                app.resize(args);
+            }
+            ReplayStep::Frame(time) => {
+                let args = DrawArgs {
+                    view: target.create_color_view(0),
+                    device: &device,
+                    queue: &queue,
+                    context: &context,
+                    time,
+                };
+                app.draw(args);
+                device.poll(wgpu::Maintain::Wait);
+            }
+        }
+    }
+    Ok(app)
+}
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use winit::event::ElementState;
+
+    use crate::RenderNextStep;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Noop;
+    impl Update for Noop {
+        fn update(&mut self, _args: UpdateArgs) -> RenderNextStep {
+            RenderNextStep {
+                should_request_redraw: false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let size = WndSize {
+            width: 64,
+            height: 48,
+        };
+        let mut recorder = Recorder::new(vec![]);
+        let start = FrameTime::now();
+        let frame = FrameTime {
+            instant: start.instant + Duration::from_millis(16),
+            system: start.system + Duration::from_millis(16),
+        };
+        recorder.record(start, RecordEvent::Init(size)).unwrap();
+        let key = RecordEvent::Key {
+            key: KeyCode::KeyW,
+            state: ElementState::Pressed,
+            repeat: false,
+        };
+        recorder.record(start, key).unwrap();
+        recorder.record(frame, RecordEvent::Frame).unwrap();
+        let buf = recorder.into_inner();
+
+        let mut replay = Replay::read(buf.as_slice()).unwrap();
+        assert_eq!(replay.wnd_size(), size);
+        let init = replay.init_time();
+        let mut context = RenderContext::new();
+        let Some(ReplayStep::Frame(time)) = replay.step(&mut Noop, &mut context) else {
+            panic!();
+        };
+        assert!(context.input.is_key_pressed(KeyCode::KeyW));
+        assert_eq!(time.instant - init.instant, Duration::from_millis(16));
+        assert_eq!(
+            time.system.duration_since(init.system).unwrap(),
+            Duration::from_millis(16)
+        );
+        assert!(replay.step(&mut Noop, &mut context).is_none());
+    }
+}
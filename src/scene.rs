@@ -0,0 +1,316 @@
+use crate::transform::{identity, inverse_affine, TransformMatrix, Trs};
+
+/// handle to a node in a [`SceneGraph`]; stale once the node is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    local: Trs,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    drawable: Option<T>,
+    world: TransformMatrix,
+    /// `world` is stale; a dirty node only ever has dirty descendants
+    is_dirty: bool,
+}
+
+/// nodes with local transforms relative to their parents, and optionally something to draw
+#[derive(Debug, Clone)]
+pub struct SceneGraph<T> {
+    nodes: Vec<Option<Node<T>>>,
+    roots: Vec<NodeId>,
+}
+impl<T> SceneGraph<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            roots: vec![],
+        }
+    }
+
+    /// `None` for a root node
+    ///
+    /// # Panics
+    ///
+    /// If `parent` has been removed
+    pub fn insert(&mut self, parent: Option<NodeId>, local: Trs, drawable: Option<T>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Some(Node {
+            local,
+            parent,
+            children: vec![],
+            drawable,
+            world: identity(),
+            is_dirty: true,
+        }));
+        self.siblings_mut(parent).push(id);
+        id
+    }
+    /// removes the node with its whole subtree, returning the node's drawable
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        let parent = self.node(id)?.parent;
+        self.siblings_mut(parent).retain(|c| *c != id);
+        let mut stack = vec![id];
+        let mut removed = None;
+        while let Some(id) = stack.pop() {
+            let node = self.nodes[id.0].take().expect("children are alive");
+            stack.extend(node.children);
+            if removed.is_none() {
+                removed = Some(node.drawable);
+            }
+        }
+        removed.flatten()
+    }
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.node(id).is_some()
+    }
+    pub fn len(&self) -> usize {
+        self.nodes.iter().flatten().count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id)?.parent
+    }
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.node(id).map(|n| &n.children[..]).unwrap_or_default()
+    }
+    pub fn drawable(&self, id: NodeId) -> Option<&T> {
+        self.node(id)?.drawable.as_ref()
+    }
+    pub fn drawable_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.node_mut(id)?.drawable.as_mut()
+    }
+
+    pub fn local(&self, id: NodeId) -> Option<&Trs> {
+        Some(&self.node(id)?.local)
+    }
+    /// invalidates the world matrices of the node and its descendants
+    pub fn set_local(&mut self, id: NodeId, local: Trs) {
+        let Some(node) = self.node_mut(id) else {
+            return;
+        };
+        node.local = local;
+        self.mark_dirty(id);
+    }
+    /// local-to-world matrix, recomputing it and its stale ancestors on demand
+    pub fn world(&mut self, id: NodeId) -> Option<&TransformMatrix> {
+        let node = self.node(id)?;
+        if node.is_dirty {
+            let parent_world = match node.parent {
+                Some(parent) => self.world(parent)?.clone(),
+                None => identity(),
+            };
+            let node = self.node_mut(id)?;
+            node.world = parent_world.mul_matrix_square(&node.local.matrix());
+            node.is_dirty = false;
+        }
+        Some(&self.node(id)?.world)
+    }
+    /// recomputes every stale world matrix, each at most once
+    pub fn update(&mut self) {
+        let mut stack = self.roots.clone();
+        while let Some(id) = stack.pop() {
+            let Some(node) = self.node(id) else {
+                continue;
+            };
+            stack.extend(node.children.iter().copied());
+            if node.is_dirty {
+                self.world(id);
+            }
+        }
+    }
+
+    /// Moves `id` under `parent`, or to the roots for `None`, without moving it in the world.
+    ///
+    /// Fails if `parent` is `id` or one of its descendants, or if the new local transform has
+    /// shear that [`Trs`] cannot hold.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> anyhow::Result<()> {
+        if !self.contains(id) {
+            anyhow::bail!("node {id:?} does not exist");
+        }
+        if let Some(parent) = parent {
+            if !self.contains(parent) {
+                anyhow::bail!("parent {parent:?} does not exist");
+            }
+            let mut ancestor = Some(parent);
+            while let Some(a) = ancestor {
+                if a == id {
+                    anyhow::bail!("node {id:?} cannot be parented to its own subtree");
+                }
+                ancestor = self.parent(a);
+            }
+        }
+        let world = self.world(id).expect("checked above").clone();
+        let local = match parent {
+            Some(parent) => {
+                let parent_world = self.world(parent).expect("checked above");
+                let Some(inverse) = inverse_affine(parent_world) else {
+                    anyhow::bail!("parent {parent:?} has a singular world matrix");
+                };
+                inverse.mul_matrix_square(&world)
+            }
+            None => world,
+        };
+        let Some(local) = Trs::decompose(&local) else {
+            anyhow::bail!("node {id:?} would need shear under {parent:?}");
+        };
+
+        let old_parent = self.parent(id);
+        self.siblings_mut(old_parent).retain(|c| *c != id);
+        self.siblings_mut(parent).push(id);
+        let node = self.node_mut(id).expect("checked above");
+        node.parent = parent;
+        node.local = local;
+        self.mark_dirty(id);
+        Ok(())
+    }
+
+    /// every node with a drawable, with its up-to-date world matrix
+    pub fn drawables(&mut self) -> impl Iterator<Item = (NodeId, &T, &TransformMatrix)> {
+        self.update();
+        self.nodes.iter().enumerate().filter_map(|(i, node)| {
+            let node = node.as_ref()?;
+            Some((NodeId(i), node.drawable.as_ref()?, &node.world))
+        })
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node<T>> {
+        self.nodes.get(id.0)?.as_ref()
+    }
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node<T>> {
+        self.nodes.get_mut(id.0)?.as_mut()
+    }
+    fn siblings_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent {
+            Some(parent) => {
+                &mut self
+                    .node_mut(parent)
+                    .expect("parent does not exist")
+                    .children
+            }
+            None => &mut self.roots,
+        }
+    }
+    fn mark_dirty(&mut self, id: NodeId) {
+        let node = self.node_mut(id).expect("checked by callers");
+        node.is_dirty = true;
+        let mut stack = node.children.clone();
+        while let Some(id) = stack.pop() {
+            let node = self.node_mut(id).expect("children are alive");
+            // a dirty node's subtree is dirty already
+            if node.is_dirty {
+                continue;
+            }
+            node.is_dirty = true;
+            stack.extend(node.children.iter().copied());
+        }
+    }
+}
+impl<T> Default for SceneGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::transform::{transform_point, Quaternion};
+
+    use super::*;
+
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a:?} != {b:?}"
+        );
+    }
+    fn origin<T>(scene: &mut SceneGraph<T>, id: NodeId) -> [f64; 3] {
+        transform_point(scene.world(id).unwrap(), [0.; 3])
+    }
+
+    #[test]
+    fn test_propagation() {
+        let mut scene = SceneGraph::new();
+        let sun = scene.insert(None, Trs::from_translation([1., 0., 0.]), None);
+        let planet = scene.insert(
+            Some(sun),
+            Trs::from_translation([2., 0., 0.]),
+            Some("planet"),
+        );
+        let moon = scene.insert(
+            Some(planet),
+            Trs::from_translation([0., 0., 1.]),
+            Some("moon"),
+        );
+        assert_close(origin(&mut scene, moon), [3., 0., 1.]);
+
+        let mut spun = Trs::from_translation([1., 0., 0.]);
+        spun.rotation = Quaternion::from_axis_angle([0., 1., 0.], PI / 2.);
+        scene.set_local(sun, spun);
+        assert!(scene.node(moon).unwrap().is_dirty);
+        assert_close(origin(&mut scene, planet), [1., 0., -2.]);
+        assert_close(origin(&mut scene, moon), [2., 0., -2.]);
+
+        let drawn = scene.drawables().map(|(_, d, _)| *d).collect::<Vec<_>>();
+        assert_eq!(drawn, ["planet", "moon"]);
+        assert!(scene.nodes.iter().flatten().all(|n| !n.is_dirty));
+    }
+
+    #[test]
+    fn test_reparent() {
+        let mut scene = SceneGraph::<()>::new();
+        let a = scene.insert(
+            None,
+            Trs {
+                translation: [1., 2., 3.],
+                rotation: Quaternion::from_axis_angle([1., 0., 0.], 0.7),
+                scale: [2.; 3],
+            },
+            None,
+        );
+        let b = scene.insert(None, Trs::from_translation([-1., 0., 4.]), None);
+        let c = scene.insert(Some(b), Trs::from_translation([0., 1., 0.]), None);
+        let before = origin(&mut scene, c);
+
+        scene.set_parent(c, Some(a)).unwrap();
+        assert_eq!(scene.parent(c), Some(a));
+        assert_eq!(scene.children(a), [c]);
+        assert!(scene.children(b).is_empty());
+        assert_close(origin(&mut scene, c), before);
+
+        scene.set_parent(c, None).unwrap();
+        assert_eq!(scene.roots(), [a, b, c]);
+        assert_close(origin(&mut scene, c), before);
+
+        scene.set_parent(b, Some(a)).unwrap();
+        assert!(scene.set_parent(a, Some(b)).is_err());
+        assert!(scene.set_parent(a, Some(a)).is_err());
+
+        scene.remove(a);
+        assert!(!scene.contains(b));
+        assert_eq!(scene.roots(), [c]);
+        assert_eq!(scene.len(), 1);
+
+        // a micrometer-sized parent is still invertible
+        let tiny = scene.insert(
+            None,
+            Trs {
+                scale: [1e-6; 3],
+                ..Trs::from_translation([5., 0., 0.])
+            },
+            None,
+        );
+        let before = origin(&mut scene, c);
+        scene.set_parent(c, Some(tiny)).unwrap();
+        assert_close(origin(&mut scene, c), before);
+    }
+}
@@ -0,0 +1,318 @@
+use bytemuck_derive::{Pod, Zeroable};
+
+use crate::{
+    camera::projection::Projection,
+    texture::{CubeTexture, DepthBuffer, ImageSampler, SamplerOptions},
+    transform::{transform_size, Mat4, TransformMatrix},
+};
+
+const SHADER: &str = include_str!("skybox.wgsl");
+/// one triangle covering the screen, generated in the vertex shader
+const VERTEX_COUNT: u32 = 3;
+
+/// a [`CubeTexture`] around the camera, drawn into pixels nothing else has covered
+#[derive(Debug)]
+pub struct Skybox {
+    cube: CubeTexture,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+impl Skybox {
+    /// `depth_buffer` only sets the depth order; later buffers of the same order work too
+    pub fn new(
+        device: &wgpu::Device,
+        cube: CubeTexture,
+        color_format: wgpu::TextureFormat,
+        depth_buffer: &DepthBuffer,
+    ) -> Self {
+        let options = SamplerOptions::trilinear();
+        let sampler = ImageSampler::with_options(device, options, Some("skybox"))
+            .expect("trilinear options are valid");
+        let desc = wgpu::ShaderModuleDescriptor {
+            label: Some("skybox"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        };
+        let shader = device.create_shader_module(desc);
+        let desc = wgpu::BufferDescriptor {
+            label: Some("skybox"),
+            size: core::mem::size_of::<Uniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let uniform_buffer = device.create_buffer(&desc);
+        let bind_group_bindings = [
+            (
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ),
+            (
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: cube.texture_layout(),
+                    count: None,
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(cube.view()),
+                },
+            ),
+            (
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: sampler.sampler_layout(),
+                    count: None,
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler.sampler()),
+                },
+            ),
+        ];
+        let desc = wgpu::BindGroupLayoutDescriptor {
+            label: Some("skybox"),
+            entries: &bind_group_bindings
+                .iter()
+                .map(|(x, _)| *x)
+                .collect::<Vec<_>>(),
+        };
+        let bind_group_layout = device.create_bind_group_layout(&desc);
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("skybox"),
+            layout: &bind_group_layout,
+            entries: &bind_group_bindings
+                .iter()
+                .map(|(_, x)| x.clone())
+                .collect::<Vec<_>>(),
+        };
+        let bind_group = device.create_bind_group(&desc);
+        let desc = wgpu::PipelineLayoutDescriptor {
+            label: Some("skybox"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        };
+        let layout = device.create_pipeline_layout(&desc);
+        let desc = wgpu::RenderPipelineDescriptor {
+            label: Some("skybox"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(depth_buffer.far_state()),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(color_format.into())],
+            }),
+            multiview: None,
+            cache: None,
+        };
+        let pipeline = device.create_render_pipeline(&desc);
+        Self {
+            cube,
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn cube(&self) -> &CubeTexture {
+        &self.cube
+    }
+
+    /// Draws after the opaque geometry, into `target` and its loaded `depth_buffer`.
+    ///
+    /// Only the rotation of `view` is used, so the sky stays put as the camera moves.
+    /// Orthographic projections see one direction on every pixel.
+    /// Nothing is drawn if `view` and `projection` are singular.
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        depth_buffer: &DepthBuffer,
+        view: &Mat4,
+        projection: &Projection,
+    ) {
+        let size = depth_buffer.texture().size();
+        let aspect = size.width as f64 / size.height as f64;
+        let Some(clip_to_direction) = clip_to_direction(view, projection, aspect) else {
+            return;
+        };
+        let uniform = Uniform {
+            clip_to_direction: clip_to_direction.to_cols_f32(),
+            near_depth: projection.near_depth() as f32,
+            mid_depth: projection.mid_depth() as f32,
+            far_depth: depth_buffer.far(),
+            _padding: 0,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+        let desc = wgpu::CommandEncoderDescriptor {
+            label: Some("skybox"),
+        };
+        let mut command = device.create_command_encoder(&desc);
+        {
+            let color = wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            };
+            let desc = wgpu::RenderPassDescriptor {
+                label: Some("skybox"),
+                color_attachments: &[Some(color)],
+                depth_stencil_attachment: Some(depth_buffer.attachment_load()),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            };
+            let mut pass = command.begin_render_pass(&desc);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.draw(0..VERTEX_COUNT, 0..1);
+        }
+        queue.submit([command.finish()]);
+    }
+}
+
+/// Inverse of `projection` after the rotation of `view`.
+///
+/// Unprojects NDC into directions around the camera; the sky shader takes the difference of two
+/// depths, which is the constant view direction for orthographic projections.
+fn clip_to_direction(view: &Mat4, projection: &Projection, aspect: f64) -> Option<Mat4> {
+    let mut rotation = view.matrix().clone().into_buffer();
+    for row in 0..3 {
+        rotation[row * 4 + 3] = 0.;
+    }
+    let rotation = Mat4::from(TransformMatrix::new(transform_size(), rotation));
+    (Mat4::from(projection.matrix(aspect)) * rotation).inverse()
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Uniform {
+    pub clip_to_direction: [[f32; 4]; 4],
+    pub near_depth: f32,
+    pub mid_depth: f32,
+    pub far_depth: f32,
+    pub _padding: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        texture::{readback::read_rgba8, RenderTarget, TextureOptions},
+        transform::{look_at, DepthOrder, Point3, Vec3},
+        WndSize,
+    };
+
+    use super::*;
+
+    /// what the sky shader samples at `ndc`
+    fn direction(clip_to_direction: &Mat4, projection: &Projection, ndc: [f64; 2]) -> Vec3 {
+        let near = clip_to_direction * Point3::new(ndc[0], ndc[1], projection.near_depth());
+        let mid = clip_to_direction * Point3::new(ndc[0], ndc[1], projection.mid_depth());
+        (mid - near).normalize().unwrap()
+    }
+
+    #[test]
+    fn test_clip_to_direction() {
+        let view = Mat4::from(look_at([5., 1., 2.], [5., 1., 1.], [0., 1., 0.]));
+        let forward = Vec3::new(0., 0., -1.);
+
+        let projection = Projection::perspective(0.5, 0.1, None);
+        let m = clip_to_direction(&view, &projection, 2.).unwrap();
+        let center = direction(&m, &projection, [0., 0.]);
+        assert!((center - forward).length() < 1e-6);
+        let corner = direction(&m, &projection, [1., 1.]);
+        assert!(1e-3 < (corner - forward).length());
+
+        // the whole screen looks one way rather than at a small square of sky
+        let projection = Projection::orthographic(2., 0.1, 10.);
+        let m = clip_to_direction(&view, &projection, 2.).unwrap();
+        for ndc in [[0., 0.], [1., 1.], [-1., 0.5]] {
+            let d = direction(&m, &projection, ndc);
+            assert!((d - forward).length() < 1e-6, "{d:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orthographic_sky_fills_screen() {
+        use crate::gpu::{adapter, device, instance};
+
+        let instance = instance();
+        let adapter = adapter(&instance, None).await.unwrap();
+        let (device, queue) = device(&adapter).await.unwrap();
+        // a solid color per face, -Z last
+        let colors = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 0, 255],
+            [0, 255, 255, 255],
+            [255, 0, 255, 255],
+        ];
+        let faces = colors.map(|c| image::RgbaImage::from_pixel(2, 2, image::Rgba(c)).into());
+        let cube =
+            CubeTexture::from_images(&device, faces, TextureOptions::default(), None).unwrap();
+        cube.register(&queue);
+        let size = WndSize {
+            width: 8,
+            height: 4,
+        };
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let target =
+            RenderTarget::new(&device, size, &[format], Some(DepthOrder::Standard), None).unwrap();
+        let depth_buffer = target.depth_buffer().unwrap();
+        let skybox = Skybox::new(&device, cube, format, depth_buffer);
+
+        let desc = wgpu::CommandEncoderDescriptor { label: None };
+        let mut command = device.create_command_encoder(&desc);
+        {
+            let color_attachments =
+                target.color_attachments(wgpu::LoadOp::Clear(wgpu::Color::BLACK));
+            let desc = wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &color_attachments,
+                depth_stencil_attachment: Some(depth_buffer.attachment_clear()),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            };
+            let _ = command.begin_render_pass(&desc);
+        }
+        queue.submit([command.finish()]);
+        let view = Mat4::from(look_at([0., 0., 3.], [0., 0., 0.], [0., 1., 0.]));
+        let projection = Projection::orthographic(2., 0.1, 10.);
+        skybox.draw(
+            &device,
+            &queue,
+            target.color_view(0),
+            depth_buffer,
+            &view,
+            &projection,
+        );
+        let image = read_rgba8(&device, &queue, target.color_texture(0)).unwrap();
+        assert!(image.pixels().all(|p| p.0 == colors[5]));
+    }
+}
@@ -0,0 +1,46 @@
+struct Uniform {
+    clip_to_direction: mat4x4<f32>,
+    near_depth: f32,
+    mid_depth: f32,
+    far_depth: f32,
+    padding: u32,
+}
+@group(0)
+@binding(0)
+var<uniform> uniform: Uniform;
+@group(0)
+@binding(1)
+var sky: texture_cube<f32>;
+@group(0)
+@binding(2)
+var sampl: sampler;
+
+struct Fragment {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> Fragment {
+    // (-1, -1), (3, -1) and (-1, 3) cover the screen
+    let ndc = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+
+    var fragment = Fragment();
+    // on the far plane, so the depth test only passes where nothing was drawn
+    fragment.position = vec4<f32>(ndc, uniform.far_depth, 1.0);
+    fragment.ndc = ndc;
+    return fragment;
+}
+
+fn unproject(ndc: vec2<f32>, depth: f32) -> vec3<f32> {
+    let p = uniform.clip_to_direction * vec4<f32>(ndc, depth, 1.0);
+    return p.xyz / p.w;
+}
+
+@fragment
+fn fs_main(fragment: Fragment) -> @location(0) vec4<f32> {
+    // both depths stay finite, even with an infinite far plane
+    let near = unproject(fragment.ndc, uniform.near_depth);
+    let mid = unproject(fragment.ndc, uniform.mid_depth);
+    return textureSample(sky, sampl, mid - near);
+}
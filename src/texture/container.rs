@@ -0,0 +1,402 @@
+//! KTX2 and DDS files holding ready-to-upload mip levels
+
+const KTX2_MAGIC: [u8; 12] = *b"\xabKTX 20\xbb\r\n\x1a\n";
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+/// largest width or height accepted from a header, beyond any device's 2D texture limit
+pub const MAX_DIMENSION: u32 = 1 << 15;
+
+/// 2D image in a GPU format with its mip levels, as stored in a container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerImage {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// level 0 first, each in `format`'s block layout
+    pub levels: Vec<Vec<u8>>,
+}
+impl ContainerImage {
+    pub fn is_container(bytes: &[u8]) -> bool {
+        bytes.starts_with(&KTX2_MAGIC) || bytes.starts_with(&DDS_MAGIC)
+    }
+    /// KTX2 or DDS, told apart by their magic numbers
+    pub fn parse(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.starts_with(&KTX2_MAGIC) {
+            parse_ktx2(bytes)
+        } else if bytes.starts_with(&DDS_MAGIC) {
+            parse_dds(bytes)
+        } else {
+            anyhow::bail!("neither a KTX2 nor a DDS file")
+        }
+    }
+}
+
+/// byte length of mip `level` of a `width` by `height` image in `format`
+pub fn level_size(
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    level: u32,
+) -> anyhow::Result<usize> {
+    let (block_width, block_height) = format.block_dimensions();
+    let level_width = width.checked_shr(level).unwrap_or(0).max(1);
+    let level_height = height.checked_shr(level).unwrap_or(0).max(1);
+    let blocks_wide = level_width.div_ceil(block_width) as usize;
+    let blocks_high = level_height.div_ceil(block_height) as usize;
+    let block_size = format.block_copy_size(None).expect("color") as usize;
+    blocks_wide
+        .checked_mul(blocks_high)
+        .and_then(|blocks| blocks.checked_mul(block_size))
+        .ok_or_else(|| anyhow::anyhow!("{width}x{height} {format:?} level {level} is too large"))
+}
+/// rejects sizes no device could hold and more levels than the mip chain has
+fn check_extent(width: u32, height: u32, level_count: u32) -> anyhow::Result<()> {
+    if width == 0 || height == 0 || MAX_DIMENSION < width || MAX_DIMENSION < height {
+        anyhow::bail!("{width}x{height} is outside 1x1 to {MAX_DIMENSION}x{MAX_DIMENSION}");
+    }
+    let max_levels = width.max(height).ilog2() + 1;
+    if max_levels < level_count {
+        anyhow::bail!(
+            "{level_count} mip levels exceed the {max_levels} of a {width}x{height} image"
+        );
+    }
+    Ok(())
+}
+/// `bytes[offset..offset + length]`, failing instead of overflowing
+fn slice_at(bytes: &[u8], offset: u64, length: u64) -> Option<&[u8]> {
+    let start = usize::try_from(offset).ok()?;
+    let end = start.checked_add(usize::try_from(length).ok()?)?;
+    bytes.get(start..end)
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> anyhow::Result<u32> {
+    let Some(b) = bytes.get(at..at + 4) else {
+        anyhow::bail!("truncated at byte {at}");
+    };
+    Ok(u32::from_le_bytes(b.try_into().expect("4 bytes")))
+}
+fn u64_at(bytes: &[u8], at: usize) -> anyhow::Result<u64> {
+    let Some(b) = bytes.get(at..at + 8) else {
+        anyhow::bail!("truncated at byte {at}");
+    };
+    Ok(u64::from_le_bytes(b.try_into().expect("8 bytes")))
+}
+
+fn parse_ktx2(bytes: &[u8]) -> anyhow::Result<ContainerImage> {
+    let vk_format = u32_at(bytes, 12)?;
+    let width = u32_at(bytes, 20)?;
+    let height = u32_at(bytes, 24)?;
+    let depth = u32_at(bytes, 28)?;
+    let layers = u32_at(bytes, 32)?;
+    let faces = u32_at(bytes, 36)?;
+    let level_count = u32_at(bytes, 40)?.max(1);
+    let supercompression = u32_at(bytes, 44)?;
+    if supercompression != 0 {
+        anyhow::bail!("KTX2 supercompression scheme {supercompression} is not supported");
+    }
+    if 1 < depth || 1 < layers || faces != 1 {
+        anyhow::bail!("only plain 2D KTX2 textures are supported");
+    }
+    let Some(format) = vk_format_to_wgpu(vk_format) else {
+        anyhow::bail!("unsupported KTX2 vkFormat {vk_format}");
+    };
+    check_extent(width, height, level_count)?;
+
+    let mut levels = vec![];
+    for level in 0..level_count {
+        let entry = 80 + level as usize * 24;
+        let offset = u64_at(bytes, entry)?;
+        let length = u64_at(bytes, entry + 8)?;
+        let expected = level_size(format, width, height, level)?;
+        if length != expected as u64 {
+            anyhow::bail!("KTX2 level {level} has {length} bytes instead of {expected}");
+        }
+        let Some(data) = slice_at(bytes, offset, length) else {
+            anyhow::bail!("KTX2 level {level} runs past the end of the file");
+        };
+        levels.push(data.to_vec());
+    }
+    Ok(ContainerImage {
+        format,
+        width,
+        height,
+        levels,
+    })
+}
+
+fn parse_dds(bytes: &[u8]) -> anyhow::Result<ContainerImage> {
+    const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDPF_RGB: u32 = 0x40;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+    const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+
+    if u32_at(bytes, 4)? != 124 {
+        anyhow::bail!("malformed DDS header");
+    }
+    let flags = u32_at(bytes, 8)?;
+    let height = u32_at(bytes, 12)?;
+    let width = u32_at(bytes, 16)?;
+    let level_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        u32_at(bytes, 28)?.max(1)
+    } else {
+        1
+    };
+    let pixel_flags = u32_at(bytes, 80)?;
+    let four_cc = bytes.get(84..88).unwrap_or_default();
+    let caps2 = u32_at(bytes, 112)?;
+    if caps2 & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+        anyhow::bail!("only plain 2D DDS textures are supported");
+    }
+
+    let mut data_start = 128;
+    let format = if pixel_flags & DDPF_FOURCC != 0 {
+        if four_cc == b"DX10" {
+            data_start += 20;
+            let dxgi_format = u32_at(bytes, 128)?;
+            let array_size = u32_at(bytes, 140)?;
+            if 1 < array_size {
+                anyhow::bail!("DDS texture arrays are not supported");
+            }
+            dxgi_format_to_wgpu(dxgi_format)
+                .ok_or_else(|| anyhow::anyhow!("unsupported DXGI format {dxgi_format}"))?
+        } else {
+            four_cc_to_wgpu(four_cc).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "unsupported DDS FourCC {:?}",
+                    String::from_utf8_lossy(four_cc)
+                )
+            })?
+        }
+    } else if pixel_flags & DDPF_RGB != 0 {
+        let masks = [92, 96, 100, 104]
+            .map(|at| u32_at(bytes, at))
+            .into_iter()
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if u32_at(bytes, 88)? != 32 || masks != [0xff, 0xff00, 0xff_0000, 0xff00_0000] {
+            anyhow::bail!("only RGBA8 uncompressed DDS textures are supported");
+        }
+        wgpu::TextureFormat::Rgba8Unorm
+    } else {
+        anyhow::bail!("unsupported DDS pixel format");
+    };
+    check_extent(width, height, level_count)?;
+
+    let mut levels = vec![];
+    let mut offset = data_start;
+    for level in 0..level_count {
+        let length = level_size(format, width, height, level)?;
+        let Some(data) = slice_at(bytes, offset as u64, length as u64) else {
+            anyhow::bail!("DDS level {level} runs past the end of the file");
+        };
+        levels.push(data.to_vec());
+        offset += length;
+    }
+    Ok(ContainerImage {
+        format,
+        width,
+        height,
+        levels,
+    })
+}
+
+fn vk_format_to_wgpu(vk_format: u32) -> Option<wgpu::TextureFormat> {
+    use wgpu::{AstcBlock as B, AstcChannel as C, TextureFormat as F};
+
+    Some(match vk_format {
+        37 => F::Rgba8Unorm,
+        43 => F::Rgba8UnormSrgb,
+        97 => F::Rgba16Float,
+        109 => F::Rgba32Float,
+        131 | 133 => F::Bc1RgbaUnorm,
+        132 | 134 => F::Bc1RgbaUnormSrgb,
+        135 => F::Bc2RgbaUnorm,
+        136 => F::Bc2RgbaUnormSrgb,
+        137 => F::Bc3RgbaUnorm,
+        138 => F::Bc3RgbaUnormSrgb,
+        139 => F::Bc4RUnorm,
+        140 => F::Bc4RSnorm,
+        141 => F::Bc5RgUnorm,
+        142 => F::Bc5RgSnorm,
+        143 => F::Bc6hRgbUfloat,
+        144 => F::Bc6hRgbFloat,
+        145 => F::Bc7RgbaUnorm,
+        146 => F::Bc7RgbaUnormSrgb,
+        147 => F::Etc2Rgb8Unorm,
+        148 => F::Etc2Rgb8UnormSrgb,
+        149 => F::Etc2Rgb8A1Unorm,
+        150 => F::Etc2Rgb8A1UnormSrgb,
+        151 => F::Etc2Rgba8Unorm,
+        152 => F::Etc2Rgba8UnormSrgb,
+        153 => F::EacR11Unorm,
+        154 => F::EacR11Snorm,
+        155 => F::EacRg11Unorm,
+        156 => F::EacRg11Snorm,
+        157..=184 => {
+            let blocks = [
+                B::B4x4,
+                B::B5x4,
+                B::B5x5,
+                B::B6x5,
+                B::B6x6,
+                B::B8x5,
+                B::B8x6,
+                B::B8x8,
+                B::B10x5,
+                B::B10x6,
+                B::B10x8,
+                B::B10x10,
+                B::B12x10,
+                B::B12x12,
+            ];
+            let i = vk_format - 157;
+            let channel = if i.is_multiple_of(2) {
+                C::Unorm
+            } else {
+                C::UnormSrgb
+            };
+            F::Astc {
+                block: blocks[i as usize / 2],
+                channel,
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn dxgi_format_to_wgpu(dxgi_format: u32) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat as F;
+
+    Some(match dxgi_format {
+        2 => F::Rgba32Float,
+        10 => F::Rgba16Float,
+        28 => F::Rgba8Unorm,
+        29 => F::Rgba8UnormSrgb,
+        71 => F::Bc1RgbaUnorm,
+        72 => F::Bc1RgbaUnormSrgb,
+        74 => F::Bc2RgbaUnorm,
+        75 => F::Bc2RgbaUnormSrgb,
+        77 => F::Bc3RgbaUnorm,
+        78 => F::Bc3RgbaUnormSrgb,
+        80 => F::Bc4RUnorm,
+        81 => F::Bc4RSnorm,
+        83 => F::Bc5RgUnorm,
+        84 => F::Bc5RgSnorm,
+        95 => F::Bc6hRgbUfloat,
+        96 => F::Bc6hRgbFloat,
+        98 => F::Bc7RgbaUnorm,
+        99 => F::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+fn four_cc_to_wgpu(four_cc: &[u8]) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat as F;
+
+    Some(match four_cc {
+        b"DXT1" => F::Bc1RgbaUnorm,
+        b"DXT2" | b"DXT3" => F::Bc2RgbaUnorm,
+        b"DXT4" | b"DXT5" => F::Bc3RgbaUnorm,
+        b"ATI1" | b"BC4U" => F::Bc4RUnorm,
+        b"BC4S" => F::Bc4RSnorm,
+        b"ATI2" | b"BC5U" => F::Bc5RgUnorm,
+        b"BC5S" => F::Bc5RgSnorm,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ktx2(vk_format: u32, width: u32, height: u32, levels: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = KTX2_MAGIC.to_vec();
+        for field in [vk_format, 1, width, height, 0, 0, 1, levels.len() as u32, 0] {
+            bytes.extend(field.to_le_bytes());
+        }
+        bytes.resize(80, 0);
+        let mut offset = 80 + levels.len() * 24;
+        for level in levels {
+            for field in [offset, level.len(), level.len()] {
+                bytes.extend((field as u64).to_le_bytes());
+            }
+            offset += level.len();
+        }
+        for level in levels {
+            bytes.extend(level);
+        }
+        bytes
+    }
+    fn dds(four_cc: &[u8; 4], width: u32, height: u32, levels: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = DDS_MAGIC.to_vec();
+        let header = [124, 0x2_1007, height, width, 0, 0, levels.len() as u32];
+        for field in header {
+            bytes.extend(field.to_le_bytes());
+        }
+        bytes.resize(76, 0);
+        bytes.extend(32_u32.to_le_bytes());
+        bytes.extend(4_u32.to_le_bytes());
+        bytes.extend(four_cc);
+        bytes.resize(128, 0);
+        for level in levels {
+            bytes.extend(level);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_ktx2() {
+        let levels = vec![vec![1; 32], vec![2; 8], vec![3; 8], vec![4; 8]];
+        let bytes = ktx2(147, 8, 8, &levels);
+        let image = ContainerImage::parse(&bytes).unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Etc2Rgb8Unorm);
+        assert_eq!((image.width, image.height), (8, 8));
+        assert_eq!(image.levels, levels);
+
+        assert!(ContainerImage::parse(&ktx2(147, 8, 8, &levels[..1])[..100]).is_err());
+        assert!(ContainerImage::parse(&ktx2(147, 8, 8, &[vec![0; 31]])).is_err());
+        assert!(ContainerImage::parse(&ktx2(0, 8, 8, &levels)).is_err());
+        assert_eq!(
+            vk_format_to_wgpu(184),
+            Some(wgpu::TextureFormat::Astc {
+                block: wgpu::AstcBlock::B12x12,
+                channel: wgpu::AstcChannel::UnormSrgb,
+            })
+        );
+    }
+
+    #[test]
+    fn test_dds() {
+        let levels = vec![vec![1; 32], vec![2; 16], vec![3; 16]];
+        let bytes = dds(b"DXT5", 8, 4, &levels);
+        assert!(ContainerImage::is_container(&bytes));
+        let image = ContainerImage::parse(&bytes).unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Bc3RgbaUnorm);
+        assert_eq!((image.width, image.height), (8, 4));
+        assert_eq!(image.levels, levels);
+
+        assert!(ContainerImage::parse(&dds(b"DXT5", 8, 8, &levels)).is_err());
+        assert!(ContainerImage::parse(&dds(b"ABCD", 8, 4, &levels)).is_err());
+        assert!(!ContainerImage::is_container(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_hostile_headers() {
+        let huge = ktx2(147, u32::MAX, u32::MAX, &[vec![0; 8]]);
+        assert!(ContainerImage::parse(&huge).is_err());
+        let huge = dds(b"DXT5", u32::MAX, u32::MAX, &[vec![0; 16]]);
+        assert!(ContainerImage::parse(&huge).is_err());
+        let empty = dds(b"DXT5", 0, 4, &[vec![0; 16]]);
+        assert!(ContainerImage::parse(&empty).is_err());
+
+        let mut far = ktx2(147, 4, 4, &[vec![0; 8]]);
+        far[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(ContainerImage::parse(&far).is_err());
+        let mut too_many_levels = dds(b"DXT5", 4, 4, &[vec![0; 16]]);
+        too_many_levels[28..32].copy_from_slice(&40_u32.to_le_bytes());
+        assert!(ContainerImage::parse(&too_many_levels).is_err());
+
+        let format = wgpu::TextureFormat::Rgba32Float;
+        assert_eq!(level_size(format, 4, 2, 40).unwrap(), 16);
+        assert!(level_size(format, u32::MAX, u32::MAX, 0).is_err());
+    }
+}
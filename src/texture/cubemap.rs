@@ -0,0 +1,392 @@
+//! six square faces sampled by direction, for skyboxes and environment lighting
+
+use anyhow::Context;
+use bytemuck_derive::{Pod, Zeroable};
+
+use super::{
+    check_extent, convert, encode, mip_chain, ImageSampler, ImageTexture, SamplerOptions,
+    TextureOptions,
+};
+
+const EQUIRECT_SHADER: &str = include_str!("equirect.wgsl");
+/// faces in the layer order wgpu samples them: +X, -X, +Y, -Y, +Z, -Z
+pub const FACE_COUNT: u32 = 6;
+
+#[derive(Debug)]
+pub struct CubeTexture {
+    /// per face, level 0 first; empty if the faces were rendered on the GPU
+    faces: Vec<Vec<Vec<u8>>>,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sample_type: wgpu::TextureSampleType,
+}
+impl CubeTexture {
+    /// encoded images in [`FACE_COUNT`] order
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        faces: [&[u8]; 6],
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let mut images = vec![];
+        for (i, bytes) in faces.into_iter().enumerate() {
+            let image = image::load_from_memory(bytes)
+                .with_context(|| format!("failed to decode face {i}"))?;
+            images.push(image);
+        }
+        let images = images.try_into().expect("six faces");
+        Self::from_images(device, images, options, label)
+    }
+    /// Square faces of one size in [`FACE_COUNT`] order.
+    ///
+    /// `options.flip_vertically` is ignored: cube faces keep their first row at the top.
+    pub fn from_images(
+        device: &wgpu::Device,
+        faces: [image::DynamicImage; 6],
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let format = options.texture_format(device.features())?;
+        let size = faces[0].width();
+        if faces
+            .iter()
+            .any(|f| f.width() != size || f.height() != size)
+        {
+            anyhow::bail!("cube faces must be square and of one size");
+        }
+        check_extent(device, format, size, size)?;
+        let faces = faces
+            .into_iter()
+            .map(|face| {
+                let face = convert(face, options.format);
+                let mips = if options.mipmaps {
+                    mip_chain(face, options.color_space)
+                } else {
+                    vec![face]
+                };
+                mips.iter()
+                    .map(|mip| encode(mip, format))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        Self::create(device, format, size, usage, faces, label)
+    }
+    /// Renders an equirectangular image, typically a Radiance HDR or OpenEXR file, onto
+    /// `face_size` faces on the GPU.
+    ///
+    /// The result is already uploaded; [`Self::register`] does nothing for it.
+    pub fn from_equirect(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        face_size: u32,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let options = TextureOptions {
+            mipmaps: false,
+            flip_vertically: false,
+            ..TextureOptions::hdr()
+        };
+        let equirect = ImageTexture::from_bytes(device, bytes, options, label)?;
+        equirect.register(queue);
+        let usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT;
+        let cube = Self::create(device, equirect.format(), face_size, usage, vec![], label)?;
+
+        let options = SamplerOptions::trilinear();
+        let sampler = ImageSampler::with_options(device, options, Some("equirect"))?;
+        let desc = wgpu::ShaderModuleDescriptor {
+            label: Some("equirect"),
+            source: wgpu::ShaderSource::Wgsl(EQUIRECT_SHADER.into()),
+        };
+        let shader = device.create_shader_module(desc);
+        let desc = wgpu::BufferDescriptor {
+            label: Some("equirect face"),
+            size: core::mem::size_of::<FaceUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let uniform_buffer = device.create_buffer(&desc);
+        let desc = wgpu::BindGroupLayoutDescriptor {
+            label: Some("equirect"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: equirect.texture_layout(),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: sampler.sampler_layout(),
+                    count: None,
+                },
+            ],
+        };
+        let bind_group_layout = device.create_bind_group_layout(&desc);
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("equirect"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(equirect.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler.sampler()),
+                },
+            ],
+        };
+        let bind_group = device.create_bind_group(&desc);
+        let desc = wgpu::PipelineLayoutDescriptor {
+            label: Some("equirect"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        };
+        let layout = device.create_pipeline_layout(&desc);
+        let desc = wgpu::RenderPipelineDescriptor {
+            label: Some("equirect"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(cube.format().into())],
+            }),
+            multiview: None,
+            cache: None,
+        };
+        let pipeline = device.create_render_pipeline(&desc);
+
+        for face in 0..FACE_COUNT {
+            let uniform = FaceUniform {
+                face,
+                _padding: [0; 3],
+            };
+            queue.write_buffer(&uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+            let view = cube.face_view(face);
+            let desc = wgpu::CommandEncoderDescriptor {
+                label: Some("equirect"),
+            };
+            let mut command = device.create_command_encoder(&desc);
+            {
+                let color = wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                };
+                let desc = wgpu::RenderPassDescriptor {
+                    label: Some("equirect"),
+                    color_attachments: &[Some(color)],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                };
+                let mut pass = command.begin_render_pass(&desc);
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+            queue.submit([command.finish()]);
+        }
+        Ok(cube)
+    }
+    fn create(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: u32,
+        usage: wgpu::TextureUsages,
+        faces: Vec<Vec<Vec<u8>>>,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let sample_type = check_extent(device, format, size, size)?;
+        let mip_level_count = faces.first().map(|levels| levels.len()).unwrap_or(1);
+        let desc = wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: FACE_COUNT,
+            },
+            mip_level_count: mip_level_count as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        };
+        let texture = device.create_texture(&desc);
+        let desc = wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        };
+        let view = texture.create_view(&desc);
+        Ok(Self {
+            faces,
+            texture,
+            view,
+            sample_type,
+        })
+    }
+
+    /// uploads every mip level of every face
+    pub fn register(&self, queue: &wgpu::Queue) {
+        let block_size = self.format().block_copy_size(None).expect("color");
+        for (face, levels) in self.faces.iter().enumerate() {
+            for (level, data) in levels.iter().enumerate() {
+                let texture = wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: face as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                };
+                let size = (self.size() >> level).max(1);
+                let layout = wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size * block_size),
+                    rows_per_image: Some(size),
+                };
+                let extent = wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                };
+                queue.write_texture(texture, data, layout, extent);
+            }
+        }
+    }
+    /// width and height of each face
+    pub fn size(&self) -> u32 {
+        self.texture.width()
+    }
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+    /// one face as a 2D view; `face` in [`FACE_COUNT`] order
+    pub fn face_view(&self, face: u32) -> wgpu::TextureView {
+        let desc = wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: face,
+            array_layer_count: Some(1),
+            ..Default::default()
+        };
+        self.texture.create_view(&desc)
+    }
+    pub fn texture_layout(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Texture {
+            sample_type: self.sample_type,
+            view_dimension: wgpu::TextureViewDimension::Cube,
+            multisampled: false,
+        }
+    }
+}
+
+/// Direction sampling the texel at `u` right and `v` down of the top left of `face`.
+///
+/// Not normalized; the major axis is `1` or `-1`.
+pub fn face_direction(face: u32, u: f64, v: f64) -> [f64; 3] {
+    let s = u * 2. - 1.;
+    let t = v * 2. - 1.;
+    match face {
+        0 => [1., -t, -s],
+        1 => [-1., -t, s],
+        2 => [s, 1., t],
+        3 => [s, -1., -t],
+        4 => [s, -t, 1.],
+        _ => [-s, -t, -1.],
+    }
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct FaceUniform {
+    pub face: u32,
+    pub _padding: [u32; 3],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_face_direction() {
+        let centers = (0..FACE_COUNT)
+            .map(|face| face_direction(face, 0.5, 0.5))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            centers,
+            [
+                [1., 0., 0.],
+                [-1., 0., 0.],
+                [0., 1., 0.],
+                [0., -1., 0.],
+                [0., 0., 1.],
+                [0., 0., -1.],
+            ]
+        );
+        // the side faces share their top edges with +Y
+        assert_eq!(face_direction(4, 0.5, 0.), [0., 1., 1.]);
+        assert_eq!(face_direction(2, 0.5, 1.), [0., 1., 1.]);
+        assert_eq!(face_direction(0, 0., 0.), [1., 1., 1.]);
+        assert_eq!(face_direction(4, 1., 0.), [1., 1., 1.]);
+    }
+
+    #[tokio::test]
+    async fn test_from_equirect() {
+        use crate::gpu::{adapter, device, instance};
+
+        let instance = instance();
+        let adapter = adapter(&instance, None).await.unwrap();
+        let (device, queue) = device(&adapter).await.unwrap();
+        let equirect = image::Rgb32FImage::from_pixel(8, 4, image::Rgb([2., 1., 0.5]));
+        let mut bytes = std::io::Cursor::new(vec![]);
+        image::DynamicImage::from(equirect)
+            .write_to(&mut bytes, image::ImageFormat::OpenExr)
+            .unwrap();
+        let cube =
+            CubeTexture::from_equirect(&device, &queue, bytes.get_ref(), 4, Some("sky")).unwrap();
+        device.poll(wgpu::Maintain::Wait);
+        assert_eq!(cube.size(), 4);
+        assert_eq!(cube.format(), wgpu::TextureFormat::Rgba16Float);
+        assert!(CubeTexture::from_equirect(&device, &queue, bytes.get_ref(), 0, None).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+//! CPU decoders for block-compressed formats the device cannot sample
+
+/// decodes one 4x4 block into RGBA texels, row by row
+type DecodeBlock = fn(&[u8], &mut [[u8; 4]; 16]);
+
+/// Whether [`decompress`] has a decoder for `format`.
+///
+/// BC6H, BC7, ASTC and the signed BC4/BC5/EAC variants have none,
+/// so they only load on devices with the matching compression feature.
+pub fn can_decompress(format: wgpu::TextureFormat) -> bool {
+    decoder(format).is_some()
+}
+
+/// Decodes one mip level of `format` into the matching uncompressed format.
+///
+/// Fails for formats [`can_decompress`] rejects.
+pub fn decompress(
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> anyhow::Result<(wgpu::TextureFormat, Vec<u8>)> {
+    let Some((decoded, block_size, decode)) = decoder(format) else {
+        anyhow::bail!("no CPU decoder for {format:?}");
+    };
+    let channels = decoded.block_copy_size(None).expect("color") as usize;
+
+    let (blocks_wide, blocks_high) = (width.div_ceil(4) as usize, height.div_ceil(4) as usize);
+    let expected = blocks_wide * blocks_high * block_size;
+    if data.len() < expected {
+        anyhow::bail!(
+            "{width}x{height} {format:?} needs {expected} bytes, got {}",
+            data.len()
+        );
+    }
+    let (width, height) = (width as usize, height as usize);
+    let mut out = vec![0; width * height * channels];
+    let mut texels = [[0; 4]; 16];
+    for (i, block) in data[..expected].chunks_exact(block_size).enumerate() {
+        let (bx, by) = (i % blocks_wide * 4, i / blocks_wide * 4);
+        decode(block, &mut texels);
+        for (t, texel) in texels.iter().enumerate() {
+            let (x, y) = (bx + t % 4, by + t / 4);
+            if width <= x || height <= y {
+                continue;
+            }
+            let at = (y * width + x) * channels;
+            out[at..at + channels].copy_from_slice(&texel[..channels]);
+        }
+    }
+    Ok((decoded, out))
+}
+
+/// the uncompressed format, bytes per block and block decoder for `format`
+fn decoder(format: wgpu::TextureFormat) -> Option<(wgpu::TextureFormat, usize, DecodeBlock)> {
+    use wgpu::TextureFormat as F;
+
+    let rgba8 = if format.is_srgb() {
+        F::Rgba8UnormSrgb
+    } else {
+        F::Rgba8Unorm
+    };
+    let decoder: (_, _, DecodeBlock) = match format {
+        F::Bc1RgbaUnorm | F::Bc1RgbaUnormSrgb => (rgba8, 8, |b, out| bc1(b, out, true)),
+        F::Bc2RgbaUnorm | F::Bc2RgbaUnormSrgb => (rgba8, 16, bc2),
+        F::Bc3RgbaUnorm | F::Bc3RgbaUnormSrgb => (rgba8, 16, bc3),
+        F::Bc4RUnorm => (F::R8Unorm, 8, bc4),
+        F::Bc5RgUnorm => (F::Rg8Unorm, 16, bc5),
+        F::Etc2Rgb8Unorm | F::Etc2Rgb8UnormSrgb => (rgba8, 8, |b, out| etc2(b, out, None)),
+        F::Etc2Rgb8A1Unorm | F::Etc2Rgb8A1UnormSrgb => (rgba8, 8, etc2_punchthrough),
+        F::Etc2Rgba8Unorm | F::Etc2Rgba8UnormSrgb => (rgba8, 16, etc2_eac),
+        F::EacR11Unorm => (F::R8Unorm, 8, eac_r11),
+        F::EacRg11Unorm => (F::Rg8Unorm, 16, eac_rg11),
+        _ => return None,
+    };
+    Some(decoder)
+}
+
+fn rgb565(c: u16) -> [u8; 3] {
+    let (r, g, b) = ((c >> 11) & 31, (c >> 5) & 63, c & 31);
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+    ]
+}
+fn mix(a: [u8; 3], wa: u32, b: [u8; 3], wb: u32) -> [u8; 3] {
+    [0, 1, 2].map(|i| ((a[i] as u32 * wa + b[i] as u32 * wb) / (wa + wb)) as u8)
+}
+
+/// `punch_through` allows the three-color mode with transparent black, which BC2 and BC3 lack
+fn bc1(block: &[u8], out: &mut [[u8; 4]; 16], punch_through: bool) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let opaque = |[r, g, b]: [u8; 3]| [r, g, b, 255];
+    let palette = if c1 < c0 || !punch_through {
+        [a, b, mix(a, 2, b, 1), mix(a, 1, b, 2)].map(opaque)
+    } else {
+        [opaque(a), opaque(b), opaque(mix(a, 1, b, 1)), [0; 4]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (t, texel) in out.iter_mut().enumerate() {
+        *texel = palette[(indices >> (2 * t) & 3) as usize];
+    }
+}
+fn bc2(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    bc1(&block[8..], out, false);
+    let alpha = u64::from_le_bytes(block[..8].try_into().expect("8 bytes"));
+    for (t, texel) in out.iter_mut().enumerate() {
+        texel[3] = (alpha >> (4 * t) & 15) as u8 * 17;
+    }
+}
+fn bc3(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    bc1(&block[8..], out, false);
+    for (texel, a) in out.iter_mut().zip(bc4_channel(&block[..8])) {
+        texel[3] = a;
+    }
+}
+fn bc4(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    for (texel, r) in out.iter_mut().zip(bc4_channel(block)) {
+        *texel = [r, 0, 0, 255];
+    }
+}
+fn bc5(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    let red = bc4_channel(&block[..8]);
+    let green = bc4_channel(&block[8..]);
+    for (t, texel) in out.iter_mut().enumerate() {
+        *texel = [red[t], green[t], 0, 255];
+    }
+}
+/// the interpolated single channel shared by BC3 alpha, BC4 and BC5
+fn bc4_channel(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u32, block[1] as u32);
+    let mut palette = [0; 8];
+    palette[0] = a0;
+    palette[1] = a1;
+    if a1 < a0 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i as u32) * a0 + i as u32 * a1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i as u32) * a0 + i as u32 * a1) / 5;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+    let mut bits = [0; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    let mut out = [0; 16];
+    for (t, value) in out.iter_mut().enumerate() {
+        *value = palette[(indices >> (3 * t) & 7) as usize] as u8;
+    }
+    out
+}
+
+const ETC1_MODIFIERS: [[i32; 4]; 8] = [
+    [2, 8, -2, -8],
+    [5, 17, -5, -17],
+    [9, 29, -9, -29],
+    [13, 42, -13, -42],
+    [18, 60, -18, -60],
+    [24, 80, -24, -80],
+    [33, 106, -33, -106],
+    [47, 183, -47, -183],
+];
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn extend4(x: u64) -> i32 {
+    (x as i32 & 15) * 17
+}
+fn extend5(x: i32) -> i32 {
+    (x << 3) | (x >> 2)
+}
+fn extend6(x: u64) -> i32 {
+    let x = x as i32 & 63;
+    (x << 2) | (x >> 4)
+}
+fn extend7(x: u64) -> i32 {
+    let x = x as i32 & 127;
+    (x << 1) | (x >> 6)
+}
+fn clamp_rgb(c: [i32; 3]) -> [u8; 3] {
+    c.map(|x| x.clamp(0, 255) as u8)
+}
+fn offset(c: [i32; 3], d: i32) -> [u8; 3] {
+    clamp_rgb(c.map(|x| x + d))
+}
+
+fn etc2_punchthrough(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    etc2(block, out, Some(block[3] & 2 != 0));
+}
+/// `opaque` is `None` for plain RGB, where the same bit selects differential mode instead
+fn etc2(block: &[u8], out: &mut [[u8; 4]; 16], opaque: Option<bool>) {
+    let bits = u64::from_be_bytes(block[..8].try_into().expect("8 bytes"));
+    let is_differential = opaque.is_some() || bits >> 33 & 1 == 1;
+    let is_transparent = |index: u64| opaque == Some(false) && index == 2;
+    // texels are stored column by column
+    let index_at = |t: usize| {
+        let k = t % 4 * 4 + t / 4;
+        (bits >> (k + 16) & 1) << 1 | bits >> k & 1
+    };
+
+    let base = |shift: u32| (bits >> shift & 31) as i32;
+    let delta = |shift: u32| ((bits >> shift & 7) as i32) << 29 >> 29;
+    let (r, g, b) = (base(59), base(51), base(43));
+    let (dr, dg, db) = (delta(56), delta(48), delta(40));
+    if is_differential && !(0..32).contains(&(r + dr)) {
+        // T mode
+        let c1 = [
+            extend4((bits >> 59 & 3) << 2 | bits >> 56 & 3),
+            extend4(bits >> 52),
+            extend4(bits >> 48),
+        ];
+        let c2 = [
+            extend4(bits >> 44),
+            extend4(bits >> 40),
+            extend4(bits >> 36),
+        ];
+        let d = ETC2_DISTANCES[((bits >> 34 & 3) << 1 | bits >> 32 & 1) as usize];
+        let palette = [clamp_rgb(c1), offset(c2, d), clamp_rgb(c2), offset(c2, -d)];
+        paint(out, palette, index_at, is_transparent);
+        return;
+    }
+    if is_differential && !(0..32).contains(&(g + dg)) {
+        // H mode
+        let c1 = [
+            extend4(bits >> 59),
+            extend4((bits >> 56 & 7) << 1 | bits >> 52 & 1),
+            extend4((bits >> 51 & 1) << 3 | bits >> 47 & 7),
+        ];
+        let c2 = [
+            extend4(bits >> 43),
+            extend4(bits >> 39),
+            extend4(bits >> 35),
+        ];
+        let packed = |c: [i32; 3]| ((c[0] / 17) << 8) | ((c[1] / 17) << 4) | (c[2] / 17);
+        let d_index = (bits >> 34 & 1) << 2 | (bits >> 32 & 1) << 1;
+        let d_index = d_index | (packed(c1) >= packed(c2)) as u64;
+        let d = ETC2_DISTANCES[d_index as usize];
+        let palette = [offset(c1, d), offset(c1, -d), offset(c2, d), offset(c2, -d)];
+        paint(out, palette, index_at, is_transparent);
+        return;
+    }
+    if is_differential && !(0..32).contains(&(b + db)) {
+        // planar mode, always opaque
+        let o = [
+            extend6(bits >> 57),
+            extend7((bits >> 56 & 1) << 6 | bits >> 49 & 63),
+            extend6((bits >> 48 & 1) << 5 | (bits >> 43 & 3) << 3 | bits >> 39 & 7),
+        ];
+        let h = [
+            extend6((bits >> 34 & 31) << 1 | bits >> 32 & 1),
+            extend7(bits >> 25),
+            extend6(bits >> 19),
+        ];
+        let v = [extend6(bits >> 13), extend7(bits >> 6), extend6(bits)];
+        for (t, texel) in out.iter_mut().enumerate() {
+            let (x, y) = ((t % 4) as i32, (t / 4) as i32);
+            let c = [0, 1, 2].map(|i| (x * (h[i] - o[i]) + y * (v[i] - o[i]) + 4 * o[i] + 2) >> 2);
+            let [r, g, b] = clamp_rgb(c);
+            *texel = [r, g, b, 255];
+        }
+        return;
+    }
+
+    let (c1, c2) = if is_differential {
+        (
+            [r, g, b].map(extend5),
+            [r + dr, g + dg, b + db].map(extend5),
+        )
+    } else {
+        (
+            [
+                extend4(bits >> 60),
+                extend4(bits >> 52),
+                extend4(bits >> 44),
+            ],
+            [
+                extend4(bits >> 56),
+                extend4(bits >> 48),
+                extend4(bits >> 40),
+            ],
+        )
+    };
+    let tables = [bits >> 37 & 7, bits >> 34 & 7].map(|i| ETC1_MODIFIERS[i as usize]);
+    let is_flipped = bits >> 32 & 1 == 1;
+    for (t, texel) in out.iter_mut().enumerate() {
+        let (x, y) = (t % 4, t / 4);
+        let second = if is_flipped { 2 <= y } else { 2 <= x };
+        let (base, table) = if second {
+            (c2, tables[1])
+        } else {
+            (c1, tables[0])
+        };
+        let index = index_at(t);
+        if is_transparent(index) {
+            *texel = [0; 4];
+            continue;
+        }
+        // without the opaque bit the smallest modifiers become zero
+        let modifier = match (opaque, index) {
+            (Some(false), 0) => 0,
+            _ => table[index as usize],
+        };
+        let [r, g, b] = offset(base, modifier);
+        *texel = [r, g, b, 255];
+    }
+}
+fn paint(
+    out: &mut [[u8; 4]; 16],
+    palette: [[u8; 3]; 4],
+    index_at: impl Fn(usize) -> u64,
+    is_transparent: impl Fn(u64) -> bool,
+) {
+    for (t, texel) in out.iter_mut().enumerate() {
+        let index = index_at(t);
+        let [r, g, b] = palette[index as usize];
+        *texel = if is_transparent(index) {
+            [0; 4]
+        } else {
+            [r, g, b, 255]
+        };
+    }
+}
+
+fn etc2_eac(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    etc2(&block[8..], out, None);
+    for (texel, a) in out.iter_mut().zip(eac_channel(&block[..8], false)) {
+        texel[3] = a;
+    }
+}
+fn eac_r11(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    for (texel, r) in out.iter_mut().zip(eac_channel(block, true)) {
+        *texel = [r, 0, 0, 255];
+    }
+}
+fn eac_rg11(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    let red = eac_channel(&block[..8], true);
+    let green = eac_channel(&block[8..], true);
+    for (t, texel) in out.iter_mut().enumerate() {
+        *texel = [red[t], green[t], 0, 255];
+    }
+}
+/// `is_r11` decodes at 11 bits before narrowing, as the R11 and RG11 formats do
+fn eac_channel(block: &[u8], is_r11: bool) -> [u8; 16] {
+    let bits = u64::from_be_bytes(block[..8].try_into().expect("8 bytes"));
+    let base = (bits >> 56) as i32;
+    let multiplier = (bits >> 52 & 15) as i32;
+    let table = EAC_MODIFIERS[(bits >> 48 & 15) as usize];
+    let mut out = [0; 16];
+    for (t, value) in out.iter_mut().enumerate() {
+        // texels are stored column by column
+        let k = t % 4 * 4 + t / 4;
+        let modifier = table[(bits >> (45 - 3 * k) & 7) as usize];
+        *value = if is_r11 {
+            let scaled = if multiplier == 0 {
+                modifier
+            } else {
+                modifier * multiplier * 8
+            };
+            let v = (base * 8 + 4 + scaled).clamp(0, 2047);
+            ((v * 255 + 1023) / 2047) as u8
+        } else {
+            (base + modifier * multiplier).clamp(0, 255) as u8
+        };
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(format: wgpu::TextureFormat, block: &[u8]) -> Vec<[u8; 4]> {
+        let (decoded, data) = decompress(format, 4, 4, block).unwrap();
+        let channels = decoded.block_copy_size(None).unwrap() as usize;
+        data.chunks(channels)
+            .map(|c| {
+                let mut texel = [0, 0, 0, 255];
+                texel[..channels].copy_from_slice(c);
+                texel
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bc() {
+        // red and blue endpoints; texel t takes index t % 4
+        let indices = 0b11_10_01_00_u32.wrapping_mul(0x0101_0101);
+        let mut block = vec![0x00, 0xf8, 0x1f, 0x00];
+        block.extend(indices.to_le_bytes());
+        let texels = decode(wgpu::TextureFormat::Bc1RgbaUnorm, &block);
+        assert_eq!(texels[0], [255, 0, 0, 255]);
+        assert_eq!(texels[1], [0, 0, 255, 255]);
+        assert_eq!(texels[2], [170, 0, 85, 255]);
+        assert_eq!(texels[7], [85, 0, 170, 255]);
+
+        // swapped endpoints switch to three colors and transparent black
+        let mut swapped = vec![0x1f, 0x00, 0x00, 0xf8];
+        swapped.extend(indices.to_le_bytes());
+        let texels = decode(wgpu::TextureFormat::Bc1RgbaUnorm, &swapped);
+        assert_eq!(texels[2], [127, 0, 127, 255]);
+        assert_eq!(texels[3], [0; 4]);
+
+        // BC3 alpha ramp from 255 to 0 with index t % 8
+        let mut alpha = vec![255, 0];
+        let ramp = (0..16).fold(0_u64, |acc, t| acc | ((t % 8) as u64) << (3 * t));
+        alpha.extend(&ramp.to_le_bytes()[..6]);
+        let texels = decode(
+            wgpu::TextureFormat::Bc3RgbaUnorm,
+            &[&alpha[..], &block].concat(),
+        );
+        let alphas = texels.iter().map(|t| t[3]).take(8).collect::<Vec<_>>();
+        assert_eq!(alphas, [255, 0, 218, 182, 145, 109, 72, 36]);
+        assert_eq!(texels[0][..3], [255, 0, 0]);
+
+        let texels = decode(
+            wgpu::TextureFormat::Bc5RgUnorm,
+            &[&alpha[..], &alpha].concat(),
+        );
+        assert_eq!(texels[2], [218, 218, 0, 255]);
+    }
+
+    /// packs ETC1 differential mode with one color for both halves
+    fn etc_differential(rgb5: [u64; 3], table: u64, indices: u32) -> [u8; 8] {
+        let bits = rgb5[0] << 59 | rgb5[1] << 51 | rgb5[2] << 43 | table << 37 | table << 34;
+        let bits = bits | 1 << 33 | indices as u64;
+        bits.to_be_bytes()
+    }
+
+    #[test]
+    fn test_etc2() {
+        // all texels use index 1, the large positive modifier
+        let block = etc_differential([16, 8, 4], 0, 0x0000_ffff);
+        let texels = decode(wgpu::TextureFormat::Etc2Rgb8Unorm, &block);
+        let base = [16, 8, 4].map(extend5);
+        assert!(texels.iter().all(|t| t[..3] == offset(base, 8)));
+
+        // individual mode: different colors on the left and right halves
+        let bits = 0xf0_u64 << 56 | 0x0f << 48;
+        let texels = decode(wgpu::TextureFormat::Etc2Rgb8Unorm, &bits.to_be_bytes());
+        assert_eq!(texels[0], [255, 2, 2, 255]);
+        assert_eq!(texels[3], [2, 255, 2, 255]);
+
+        // planar with equal origin, horizontal and vertical colors is flat
+        // the spare bits push blue out of range, which is what selects planar mode
+        let mut bits = 1 << 33 | 0b111 << 45;
+        let (o, h, v) = ([20_u64, 40, 30], [20_u64, 40, 30], [20_u64, 40, 30]);
+        bits |= o[0] << 57 | (o[1] >> 6) << 56 | (o[1] & 63) << 49;
+        bits |= (o[2] >> 5) << 48 | ((o[2] >> 3) & 3) << 43 | (o[2] & 7) << 39;
+        bits |= (h[0] >> 1) << 34 | (h[0] & 1) << 32 | h[1] << 25 | h[2] << 19;
+        bits |= v[0] << 13 | v[1] << 6 | v[2];
+        let texels = decode(wgpu::TextureFormat::Etc2Rgb8Unorm, &bits.to_be_bytes());
+        let flat = [extend6(20), extend7(40), extend6(30)].map(|x| x as u8);
+        assert!(texels.iter().all(|t| t[..3] == flat && t[3] == 255));
+
+        // EAC alpha: base 128, multiplier 2, modifier row 0 index 7 (+14)
+        let alpha = (128_u64 << 56 | 2 << 52 | 0xffff_ffff_ffff).to_be_bytes();
+        let texels = decode(
+            wgpu::TextureFormat::Etc2Rgba8Unorm,
+            &[&alpha[..], &block].concat(),
+        );
+        assert!(texels.iter().all(|t| t[3] == 156));
+
+        // punch-through without the opaque bit makes index 2 transparent
+        let mut block = etc_differential([16, 8, 4], 0, 0xffff_0000);
+        block[3] &= !2;
+        let texels = decode(wgpu::TextureFormat::Etc2Rgb8A1Unorm, &block);
+        assert!(texels.iter().all(|t| *t == [0; 4]));
+    }
+
+    #[test]
+    fn test_partial_blocks() {
+        let block = etc_differential([16, 8, 4], 0, 0);
+        let (format, data) = decompress(
+            wgpu::TextureFormat::Etc2Rgb8Unorm,
+            6,
+            2,
+            &[block, block].concat(),
+        )
+        .unwrap();
+        assert_eq!(format, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(data.len(), 6 * 2 * 4);
+        assert!(decompress(wgpu::TextureFormat::Bc7RgbaUnorm, 4, 4, &[0; 16]).is_err());
+        assert!(!can_decompress(wgpu::TextureFormat::Bc6hRgbUfloat));
+        assert!(can_decompress(wgpu::TextureFormat::EacRg11Unorm));
+        assert!(decompress(wgpu::TextureFormat::Bc1RgbaUnorm, 8, 4, &[0; 8]).is_err());
+    }
+}
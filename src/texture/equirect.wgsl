@@ -0,0 +1,53 @@
+struct Face {
+    index: u32,
+    padding_0: u32,
+    padding_1: vec2<u32>,
+}
+@group(0)
+@binding(0)
+var<uniform> face: Face;
+@group(0)
+@binding(1)
+var equirect: texture_2d<f32>;
+@group(0)
+@binding(2)
+var sampl: sampler;
+
+const PI: f32 = 3.14159265358979;
+
+struct Fragment {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> Fragment {
+    // one triangle covering the whole face
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var fragment = Fragment();
+    fragment.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    fragment.uv = uv;
+    return fragment;
+}
+
+// mirrors `face_direction` in cubemap.rs
+fn direction(index: u32, uv: vec2<f32>) -> vec3<f32> {
+    let s = uv.x * 2.0 - 1.0;
+    let t = uv.y * 2.0 - 1.0;
+    switch index {
+        case 0u: { return vec3<f32>(1.0, -t, -s); }
+        case 1u: { return vec3<f32>(-1.0, -t, s); }
+        case 2u: { return vec3<f32>(s, 1.0, t); }
+        case 3u: { return vec3<f32>(s, -1.0, -t); }
+        case 4u: { return vec3<f32>(s, -t, 1.0); }
+        default: { return vec3<f32>(-s, -t, -1.0); }
+    }
+}
+
+@fragment
+fn fs_main(fragment: Fragment) -> @location(0) vec4<f32> {
+    let dir = normalize(direction(face.index, fragment.uv));
+    // longitude across, latitude from the top row down
+    let uv = vec2<f32>(atan2(dir.z, dir.x) / (2.0 * PI) + 0.5, acos(dir.y) / PI);
+    return textureSampleLevel(equirect, sampl, uv, 0.0);
+}
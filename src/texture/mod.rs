@@ -0,0 +1,849 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::{transform::DepthOrder, WndSize};
+
+pub use container::ContainerImage;
+pub use cubemap::CubeTexture;
+pub use target::RenderTarget;
+
+pub mod container;
+pub mod cubemap;
+pub mod decompress;
+pub mod readback;
+pub mod target;
+
+/// texel layout of an [`ImageTexture`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba8,
+    /// red and green only
+    Rg8,
+    /// red only
+    R8,
+    /// half floats; for HDR images
+    Rgba16Float,
+    /// Falls back to [`Self::Rgba16Float`] unless the device can filter 32-bit floats.
+    Rgba32Float,
+}
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> u32 {
+        match self {
+            PixelFormat::Rgba8 => 4,
+            PixelFormat::Rg8 => 2,
+            PixelFormat::R8 => 1,
+            PixelFormat::Rgba16Float => 8,
+            PixelFormat::Rgba32Float => 16,
+        }
+    }
+}
+
+/// how texel values are encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// decoded to linear on sampling; for color maps
+    Srgb,
+    /// sampled as stored; for normal, roughness and other data maps
+    Linear,
+}
+
+/// KTX2 and DDS files bring their own format, color space and mip levels, so only
+/// [`Self::mipmaps`] applies to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// builds the full mip chain down to 1x1 on the CPU
+    pub mipmaps: bool,
+    /// puts the first row at the bottom, where texture coordinate 0 is
+    pub flip_vertically: bool,
+    /// only [`PixelFormat::Rgba8`] has an sRGB variant
+    pub color_space: ColorSpace,
+    /// decoded images are converted to it; raw pixels must already be in it
+    pub format: PixelFormat,
+}
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            mipmaps: true,
+            flip_vertically: true,
+            color_space: ColorSpace::Srgb,
+            format: PixelFormat::Rgba8,
+        }
+    }
+}
+impl TextureOptions {
+    /// for Radiance HDR and OpenEXR images
+    pub fn hdr() -> Self {
+        Self {
+            color_space: ColorSpace::Linear,
+            format: PixelFormat::Rgba16Float,
+            ..Self::default()
+        }
+    }
+    /// what [`Self::format`] becomes on a device with `features`
+    pub fn texture_format(&self, features: wgpu::Features) -> anyhow::Result<wgpu::TextureFormat> {
+        use wgpu::TextureFormat as F;
+
+        Ok(match (self.format, self.color_space) {
+            (PixelFormat::Rgba8, ColorSpace::Srgb) => F::Rgba8UnormSrgb,
+            (PixelFormat::Rgba8, ColorSpace::Linear) => F::Rgba8Unorm,
+            (PixelFormat::Rg8, ColorSpace::Linear) => F::Rg8Unorm,
+            (PixelFormat::R8, ColorSpace::Linear) => F::R8Unorm,
+            (PixelFormat::Rgba16Float, ColorSpace::Linear) => F::Rgba16Float,
+            (PixelFormat::Rgba32Float, ColorSpace::Linear) => {
+                if features.contains(wgpu::Features::FLOAT32_FILTERABLE) {
+                    F::Rgba32Float
+                } else {
+                    F::Rgba16Float
+                }
+            }
+            (format, ColorSpace::Srgb) => anyhow::bail!("{format:?} has no sRGB variant"),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageTexture {
+    /// level 0 first, each in the texture format's block layout
+    levels: Vec<Vec<u8>>,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sample_type: wgpu::TextureSampleType,
+}
+impl ImageTexture {
+    /// encoded image bytes with the default options
+    pub fn new(device: &wgpu::Device, bytes: &[u8], label: Option<&str>) -> anyhow::Result<Self> {
+        Self::from_bytes(device, bytes, TextureOptions::default(), label)
+    }
+    /// KTX2, DDS or any format the `image` crate can decode, including HDR and EXR
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        bytes: &[u8],
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        if ContainerImage::is_container(bytes) {
+            let image = ContainerImage::parse(bytes)?;
+            return Self::from_container(device, image, options, label);
+        }
+        let image = image::load_from_memory(bytes).context("failed to decode texture")?;
+        Self::from_image(device, image, options, label)
+    }
+    pub fn from_path(
+        device: &wgpu::Device,
+        path: impl AsRef<Path>,
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("failed to read {path:?}"))?;
+        Self::from_bytes(device, &bytes, options, label)
+            .with_context(|| format!("failed to load {path:?}"))
+    }
+    /// `data` is tightly packed rows in `options.format`, top row first, floats little-endian
+    pub fn from_raw(
+        device: &wgpu::Device,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let expected = width as usize * height as usize * options.format.bytes_per_pixel() as usize;
+        if data.len() != expected {
+            anyhow::bail!(
+                "{width}x{height} {:?} needs {expected} bytes, got {}",
+                options.format,
+                data.len()
+            );
+        }
+        let floats = |size: usize, decode: fn(&[u8]) -> f32| {
+            data.chunks_exact(size).map(decode).collect::<Vec<_>>()
+        };
+        let image = match options.format {
+            PixelFormat::Rgba8 => {
+                image::RgbaImage::from_raw(width, height, data.to_vec()).map(Into::into)
+            }
+            PixelFormat::Rg8 => {
+                image::GrayAlphaImage::from_raw(width, height, data.to_vec()).map(Into::into)
+            }
+            PixelFormat::R8 => {
+                image::GrayImage::from_raw(width, height, data.to_vec()).map(Into::into)
+            }
+            PixelFormat::Rgba16Float => {
+                let texels = floats(2, |b| half::f16::from_le_bytes([b[0], b[1]]).to_f32());
+                image::Rgba32FImage::from_raw(width, height, texels).map(Into::into)
+            }
+            PixelFormat::Rgba32Float => {
+                let texels = floats(4, |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+                image::Rgba32FImage::from_raw(width, height, texels).map(Into::into)
+            }
+        };
+        let image = image.expect("length checked above");
+        Self::create(device, image, options, label)
+    }
+    /// converted to `options.format`
+    pub fn from_image(
+        device: &wgpu::Device,
+        image: image::DynamicImage,
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let image = convert(image, options.format);
+        Self::create(device, image, options, label)
+    }
+    /// Uploads the container's own mip levels.
+    ///
+    /// Block-compressed levels the device cannot sample are decompressed on the CPU.
+    /// BC6H, BC7 and ASTC have no CPU decoder, so they fail up front on devices without
+    /// the matching feature.
+    pub fn from_container(
+        device: &wgpu::Device,
+        image: ContainerImage,
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let ContainerImage {
+            mut format,
+            width,
+            height,
+            mut levels,
+        } = image;
+        if !options.mipmaps {
+            levels.truncate(1);
+        }
+        let missing = format.required_features() - device.features();
+        if !missing.is_empty() && !decompress::can_decompress(format) {
+            anyhow::bail!("{format:?} needs {missing:?}, and there is no CPU decoder for it");
+        }
+        if !missing.is_empty() {
+            tracing::debug!(?format, ?missing, "decompressing texture on the CPU");
+            let mut decoded = None;
+            for (level, data) in levels.iter_mut().enumerate() {
+                let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+                let (decoded_format, texels) = decompress::decompress(format, w, h, data)
+                    .with_context(|| format!("device lacks {missing:?}"))?;
+                *data = texels;
+                decoded = Some(decoded_format);
+            }
+            format = decoded.expect("at least one level");
+        }
+        Self::create_levels(device, format, width, height, levels, label)
+    }
+    /// `image` must already be in `options.format`
+    fn create(
+        device: &wgpu::Device,
+        image: image::DynamicImage,
+        options: TextureOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let format = options.texture_format(device.features())?;
+        let (width, height) = (image.width(), image.height());
+        check_extent(device, format, width, height)?;
+        let image = if options.flip_vertically {
+            image.flipv()
+        } else {
+            image
+        };
+        let mips = if options.mipmaps {
+            mip_chain(image, options.color_space)
+        } else {
+            vec![image]
+        };
+        let levels = mips.iter().map(|mip| encode(mip, format)).collect();
+        Self::create_levels(device, format, width, height, levels, label)
+    }
+    fn create_levels(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        levels: Vec<Vec<u8>>,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let sample_type = check_extent(device, format, width, height)?;
+        let texture_size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let desc = wgpu::TextureDescriptor {
+            label,
+            size: texture_size,
+            mip_level_count: levels.len() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        };
+        let texture = device.create_texture(&desc);
+        let desc = wgpu::TextureViewDescriptor::default();
+        let view = texture.create_view(&desc);
+        Ok(Self {
+            levels,
+            texture,
+            view,
+            sample_type,
+        })
+    }
+
+    /// uploads every mip level
+    pub fn register(&self, queue: &wgpu::Queue) {
+        let format = self.texture.format();
+        let (block_width, block_height) = format.block_dimensions();
+        let block_size = format.block_copy_size(None).expect("color");
+        for (level, data) in self.levels.iter().enumerate() {
+            let texture = wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: level as u32,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            };
+            let size = self
+                .texture
+                .size()
+                .mip_level_size(level as u32, wgpu::TextureDimension::D2)
+                .physical_size(format);
+            let layout = wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(size.width / block_width * block_size),
+                rows_per_image: Some(size.height / block_height),
+            };
+            queue.write_texture(texture, data, layout, size);
+        }
+    }
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+    /// not filterable for 32-bit floats on devices without `FLOAT32_FILTERABLE`
+    pub fn texture_layout(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Texture {
+            sample_type: self.sample_type,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    }
+}
+
+/// Checks that a `width`x`height` `format` texture can be created and sampled on `device`,
+/// before any work goes into its texels.
+fn check_extent(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> anyhow::Result<wgpu::TextureSampleType> {
+    let max = device.limits().max_texture_dimension_2d;
+    if width == 0 || height == 0 || max < width || max < height {
+        anyhow::bail!("{width}x{height} texture is outside 1x1 to {max}x{max}");
+    }
+    let (block_width, block_height) = format.block_dimensions();
+    if !width.is_multiple_of(block_width) || !height.is_multiple_of(block_height) {
+        anyhow::bail!("{width}x{height} is not a whole number of {format:?} blocks");
+    }
+    let Some(sample_type) = format.sample_type(None, Some(device.features())) else {
+        anyhow::bail!("{format:?} cannot be sampled");
+    };
+    Ok(sample_type)
+}
+
+/// `image` followed by successive halvings down to 1x1
+///
+/// sRGB images are averaged in linear light so that their mips do not darken.
+fn mip_chain(image: image::DynamicImage, color_space: ColorSpace) -> Vec<image::DynamicImage> {
+    match color_space {
+        ColorSpace::Linear => halvings(image),
+        ColorSpace::Srgb => {
+            let linear = map_rgb(image.to_rgba32f(), srgb_to_linear);
+            let mips = halvings(linear.into()).into_iter().skip(1).map(|mip| {
+                let srgb = map_rgb(mip.into_rgba32f(), linear_to_srgb);
+                image::DynamicImage::from(image::DynamicImage::from(srgb).into_rgba8())
+            });
+            // level 0 keeps its exact bytes
+            std::iter::once(image).chain(mips).collect()
+        }
+    }
+}
+fn halvings(image: image::DynamicImage) -> Vec<image::DynamicImage> {
+    let mut mips = vec![image];
+    loop {
+        let last = mips.last().expect("level 0");
+        let (width, height) = (last.width(), last.height());
+        if width <= 1 && height <= 1 {
+            return mips;
+        }
+        let next = last.resize_exact(
+            (width / 2).max(1),
+            (height / 2).max(1),
+            image::imageops::FilterType::Triangle,
+        );
+        mips.push(next);
+    }
+}
+/// applies `f` to the color channels, leaving alpha alone
+fn map_rgb(mut image: image::Rgba32FImage, f: fn(f32) -> f32) -> image::Rgba32FImage {
+    for pixel in image.pixels_mut() {
+        for c in &mut pixel.0[..3] {
+            *c = f(*c);
+        }
+    }
+    image
+}
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    }
+}
+/// `image` in `format`; the narrower formats keep the leading channels
+fn convert(image: image::DynamicImage, format: PixelFormat) -> image::DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    match format {
+        PixelFormat::Rgba8 => image.into_rgba8().into(),
+        PixelFormat::Rg8 => {
+            let rgba = image.into_rgba8();
+            image::DynamicImage::from(image::GrayAlphaImage::from_fn(width, height, |x, y| {
+                let [r, g, ..] = rgba.get_pixel(x, y).0;
+                image::LumaA([r, g])
+            }))
+        }
+        PixelFormat::R8 => {
+            let rgba = image.into_rgba8();
+            image::DynamicImage::from(image::GrayImage::from_fn(width, height, |x, y| {
+                image::Luma([rgba.get_pixel(x, y).0[0]])
+            }))
+        }
+        PixelFormat::Rgba16Float | PixelFormat::Rgba32Float => image.into_rgba32f().into(),
+    }
+}
+/// texel bytes of `image` in `format`
+fn encode(image: &image::DynamicImage, format: wgpu::TextureFormat) -> Vec<u8> {
+    match format {
+        wgpu::TextureFormat::Rgba16Float => image
+            .to_rgba32f()
+            .into_raw()
+            .into_iter()
+            .flat_map(|x| half::f16::from_f32(x).to_le_bytes())
+            .collect(),
+        wgpu::TextureFormat::Rgba32Float => image
+            .to_rgba32f()
+            .into_raw()
+            .into_iter()
+            .flat_map(f32::to_le_bytes)
+            .collect(),
+        _ => image.as_bytes().to_vec(),
+    }
+}
+
+/// how an [`ImageSampler`] addresses and filters texels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerOptions {
+    /// for the u, v and w coordinates; `Repeat` tiles and `MirrorRepeat` tiles mirrored
+    pub address_modes: [wgpu::AddressMode; 3],
+    /// only used with [`wgpu::AddressMode::ClampToBorder`]
+    pub border_color: Option<wgpu::SamplerBorderColor>,
+    pub mag_filter: wgpu::FilterMode,
+    pub min_filter: wgpu::FilterMode,
+    /// `Linear` blends between mip levels
+    pub mipmap_filter: wgpu::FilterMode,
+    /// `1` for none, up to `16`; above `1` needs every filter `Linear`
+    pub anisotropy_clamp: u16,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    /// compares against a reference value instead of filtering colors; for shadow maps
+    pub compare: Option<wgpu::CompareFunction>,
+}
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self {
+            address_modes: [wgpu::AddressMode::ClampToEdge; 3],
+            border_color: None,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy_clamp: 1,
+            lod_min_clamp: 0.,
+            lod_max_clamp: 32.,
+            compare: None,
+        }
+    }
+}
+impl SamplerOptions {
+    /// linear within and between mip levels
+    pub fn trilinear() -> Self {
+        Self {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Self::default()
+        }
+    }
+    /// [`Self::trilinear`] taking up to `clamp` samples along surfaces seen at an angle
+    pub fn anisotropic(clamp: u16) -> Self {
+        Self {
+            anisotropy_clamp: clamp,
+            ..Self::trilinear()
+        }
+    }
+    /// Percentage-closer filtering of a depth texture against `compare`.
+    ///
+    /// Clamps to the edge so lookups outside the shadow map keep the edge's result.
+    pub fn shadow(compare: wgpu::CompareFunction) -> Self {
+        Self {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(compare),
+            ..Self::default()
+        }
+    }
+    /// [`Self::address_modes`] all set to `mode`
+    pub fn with_address_mode(self, mode: wgpu::AddressMode) -> Self {
+        Self {
+            address_modes: [mode; 3],
+            ..self
+        }
+    }
+
+    /// what a shader binding for the sampler has to be declared as
+    pub fn binding_type(&self) -> wgpu::BindingType {
+        let linear = wgpu::FilterMode::Linear;
+        let ty = if self.compare.is_some() {
+            wgpu::SamplerBindingType::Comparison
+        } else if [self.mag_filter, self.min_filter, self.mipmap_filter].contains(&linear) {
+            wgpu::SamplerBindingType::Filtering
+        } else {
+            wgpu::SamplerBindingType::NonFiltering
+        };
+        wgpu::BindingType::Sampler(ty)
+    }
+    /// fails on combinations wgpu would reject on a device with `features`
+    pub fn validate(&self, features: wgpu::Features) -> anyhow::Result<()> {
+        if !(1..=16).contains(&self.anisotropy_clamp) {
+            anyhow::bail!(
+                "anisotropy clamp {} is outside 1 to 16",
+                self.anisotropy_clamp
+            );
+        }
+        let filters = [self.mag_filter, self.min_filter, self.mipmap_filter];
+        if self.anisotropy_clamp > 1 && filters.contains(&wgpu::FilterMode::Nearest) {
+            anyhow::bail!("anisotropic filtering needs every filter to be linear");
+        }
+        if !(0. <= self.lod_min_clamp && self.lod_min_clamp <= self.lod_max_clamp) {
+            anyhow::bail!(
+                "LOD clamps {}..{} are not an ascending range from 0",
+                self.lod_min_clamp,
+                self.lod_max_clamp
+            );
+        }
+        if self
+            .address_modes
+            .contains(&wgpu::AddressMode::ClampToBorder)
+        {
+            let feature = wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER;
+            if !features.contains(feature) {
+                anyhow::bail!("clamping to the border needs {feature:?}");
+            }
+            if self.border_color.is_none() {
+                anyhow::bail!("clamping to the border needs a border color");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageSampler {
+    sampler: wgpu::Sampler,
+    options: SamplerOptions,
+}
+impl ImageSampler {
+    /// default options
+    pub fn new(device: &wgpu::Device, label: Option<&str>) -> Self {
+        Self::with_options(device, SamplerOptions::default(), label)
+            .expect("default options are valid")
+    }
+    pub fn with_options(
+        device: &wgpu::Device,
+        options: SamplerOptions,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        options.validate(device.features())?;
+        let [address_mode_u, address_mode_v, address_mode_w] = options.address_modes;
+        let desc = wgpu::SamplerDescriptor {
+            label,
+            address_mode_u,
+            address_mode_v,
+            address_mode_w,
+            mag_filter: options.mag_filter,
+            min_filter: options.min_filter,
+            mipmap_filter: options.mipmap_filter,
+            lod_min_clamp: options.lod_min_clamp,
+            lod_max_clamp: options.lod_max_clamp,
+            compare: options.compare,
+            anisotropy_clamp: options.anisotropy_clamp,
+            border_color: options.border_color,
+        };
+        let sampler = device.create_sampler(&desc);
+        Ok(Self { sampler, options })
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+    pub fn options(&self) -> &SamplerOptions {
+        &self.options
+    }
+    pub fn sampler_layout(&self) -> wgpu::BindingType {
+        self.options.binding_type()
+    }
+}
+
+#[derive(Debug)]
+pub struct DepthBuffer {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    order: DepthOrder,
+}
+impl DepthBuffer {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    /// `order` has to match the projection of whatever is drawn into it
+    pub fn new(
+        device: &wgpu::Device,
+        size: WndSize,
+        order: DepthOrder,
+        label: Option<&str>,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        };
+        let desc = wgpu::TextureDescriptor {
+            label,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        let texture = device.create_texture(&desc);
+        let desc = wgpu::TextureViewDescriptor::default();
+        let view = texture.create_view(&desc);
+        Self {
+            texture,
+            view,
+            order,
+        }
+    }
+
+    pub fn order(&self) -> DepthOrder {
+        self.order
+    }
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+    /// sampleable once the pass writing it has ended, e.g. with a comparison sampler
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+    pub fn texture_layout(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Depth,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    }
+
+    pub fn state(&self) -> wgpu::DepthStencilState {
+        let depth_compare = match self.order {
+            DepthOrder::Standard => wgpu::CompareFunction::Less,
+            DepthOrder::Reversed => wgpu::CompareFunction::Greater,
+        };
+        wgpu::DepthStencilState {
+            format: Self::FORMAT,
+            depth_write_enabled: true,
+            depth_compare,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }
+    }
+
+    /// Passes only where the depth is still the cleared far value, without writing.
+    ///
+    /// For backgrounds drawn after the opaque geometry at the far plane.
+    pub fn far_state(&self) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Equal,
+            ..self.state()
+        }
+    }
+    /// the depth cleared to, and of anything on the far plane
+    pub fn far(&self) -> f32 {
+        match self.order {
+            DepthOrder::Standard => 1.,
+            DepthOrder::Reversed => 0.,
+        }
+    }
+
+    pub fn attachment_clear(&self) -> wgpu::RenderPassDepthStencilAttachment {
+        wgpu::RenderPassDepthStencilAttachment {
+            view: &self.view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(self.far()),
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }
+    }
+    pub fn attachment_load(&self) -> wgpu::RenderPassDepthStencilAttachment {
+        wgpu::RenderPassDepthStencilAttachment {
+            view: &self.view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mip_chain() {
+        let image = image::RgbaImage::from_pixel(12, 5, image::Rgba([200, 100, 50, 255]));
+        for color_space in [ColorSpace::Linear, ColorSpace::Srgb] {
+            let mips = mip_chain(image.clone().into(), color_space);
+            let sizes = mips
+                .iter()
+                .map(|m| (m.width(), m.height()))
+                .collect::<Vec<_>>();
+            assert_eq!(sizes, [(12, 5), (6, 2), (3, 1), (1, 1)]);
+            // a flat color stays flat all the way down
+            assert_eq!(mips.last().unwrap().as_bytes(), [200, 100, 50, 255]);
+        }
+
+        // black and white average to half the light, which sRGB encodes well above half
+        let checker = image::RgbaImage::from_fn(2, 2, |x, y| {
+            let v = if (x + y) % 2 == 0 { 0 } else { 255 };
+            image::Rgba([v, v, v, 255])
+        });
+        let srgb = mip_chain(checker.clone().into(), ColorSpace::Srgb);
+        assert_eq!(srgb[0].as_bytes(), checker.as_raw().as_slice());
+        assert_eq!(srgb[1].as_bytes(), [188, 188, 188, 255]);
+        let linear = mip_chain(checker.into(), ColorSpace::Linear);
+        assert!((127..=128).contains(&linear[1].as_bytes()[0]));
+    }
+
+    #[test]
+    fn test_encode_floats() {
+        let image = image::Rgba32FImage::from_pixel(2, 1, image::Rgba([1.5, -2., 0.25, 1.]));
+        let image = image::DynamicImage::from(image);
+        let half = encode(&image, wgpu::TextureFormat::Rgba16Float);
+        assert_eq!(half.len(), 2 * 8);
+        assert_eq!(half::f16::from_le_bytes([half[2], half[3]]).to_f32(), -2.);
+        let full = encode(&image, wgpu::TextureFormat::Rgba32Float);
+        assert_eq!(full[..4], 1.5_f32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_sampler_options() {
+        let binding = |options: SamplerOptions| match options.binding_type() {
+            wgpu::BindingType::Sampler(ty) => ty,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            binding(SamplerOptions::default()),
+            wgpu::SamplerBindingType::Filtering
+        );
+        let nearest = SamplerOptions {
+            mag_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        };
+        assert_eq!(binding(nearest), wgpu::SamplerBindingType::NonFiltering);
+        let shadow = SamplerOptions::shadow(wgpu::CompareFunction::LessEqual);
+        assert_eq!(binding(shadow), wgpu::SamplerBindingType::Comparison);
+
+        let features = wgpu::Features::empty();
+        let tiled = SamplerOptions::anisotropic(16).with_address_mode(wgpu::AddressMode::Repeat);
+        assert!(tiled.validate(features).is_ok());
+        let anisotropic_nearest = SamplerOptions {
+            anisotropy_clamp: 4,
+            ..nearest
+        };
+        assert!(anisotropic_nearest.validate(features).is_err());
+        let inverted_lod = SamplerOptions {
+            lod_min_clamp: 4.,
+            lod_max_clamp: 2.,
+            ..Default::default()
+        };
+        assert!(inverted_lod.validate(features).is_err());
+        let border = SamplerOptions {
+            border_color: Some(wgpu::SamplerBorderColor::OpaqueBlack),
+            ..Default::default()
+        }
+        .with_address_mode(wgpu::AddressMode::ClampToBorder);
+        assert!(border.validate(features).is_err());
+        assert!(border
+            .validate(wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_texture_format() {
+        let options = |format, color_space| TextureOptions {
+            format,
+            color_space,
+            ..Default::default()
+        };
+        assert_eq!(
+            TextureOptions::default()
+                .texture_format(wgpu::Features::empty())
+                .unwrap(),
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        );
+        assert_eq!(
+            options(PixelFormat::Rg8, ColorSpace::Linear)
+                .texture_format(wgpu::Features::empty())
+                .unwrap(),
+            wgpu::TextureFormat::Rg8Unorm
+        );
+        assert!(options(PixelFormat::R8, ColorSpace::Srgb)
+            .texture_format(wgpu::Features::empty())
+            .is_err());
+        let hdr = options(PixelFormat::Rgba32Float, ColorSpace::Linear);
+        assert_eq!(
+            hdr.texture_format(wgpu::Features::empty()).unwrap(),
+            wgpu::TextureFormat::Rgba16Float
+        );
+        assert_eq!(
+            hdr.texture_format(wgpu::Features::FLOAT32_FILTERABLE)
+                .unwrap(),
+            wgpu::TextureFormat::Rgba32Float
+        );
+    }
+}
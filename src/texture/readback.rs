@@ -0,0 +1,146 @@
+//! copying textures back to the CPU
+
+/// Copies mip level 0 of `texture` into an image, waiting for the GPU.
+///
+/// `texture` needs [`wgpu::TextureUsages::COPY_SRC`] and an RGBA8 or BGRA8 format.
+pub fn read_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<image::RgbaImage> {
+    use wgpu::TextureFormat as F;
+
+    let format = texture.format();
+    let is_bgra = match format {
+        F::Rgba8Unorm | F::Rgba8UnormSrgb => false,
+        F::Bgra8Unorm | F::Bgra8UnormSrgb => true,
+        _ => anyhow::bail!("no RGBA8 readback for {format:?}"),
+    };
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        anyhow::bail!("texture was not created with COPY_SRC");
+    }
+    let (width, height) = (texture.width(), texture.height());
+    let bytes_per_row = width * 4;
+    let padded_bytes_per_row = padded_bytes_per_row(bytes_per_row);
+    let desc = wgpu::BufferDescriptor {
+        label: Some("readback"),
+        size: padded_bytes_per_row as wgpu::BufferAddress * height as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    };
+    let buffer = device.create_buffer(&desc);
+    let desc = wgpu::CommandEncoderDescriptor {
+        label: Some("readback"),
+    };
+    let mut command = device.create_command_encoder(&desc);
+    command.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([command.finish()]);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, move |res| {
+        let _ = tx.send(res);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    rx.recv()??;
+    let mut texels = unpad_rows(
+        &slice.get_mapped_range(),
+        bytes_per_row,
+        padded_bytes_per_row,
+    );
+    buffer.unmap();
+    if is_bgra {
+        texels
+            .chunks_exact_mut(4)
+            .for_each(|texel| texel.swap(0, 2));
+    }
+    Ok(image::RgbaImage::from_raw(width, height, texels).expect("one texel per pixel"))
+}
+
+/// `bytes_per_row` rounded up to what texture-to-buffer copies require
+pub fn padded_bytes_per_row(bytes_per_row: u32) -> u32 {
+    bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+/// the first `bytes_per_row` bytes of each `padded_bytes_per_row` row
+fn unpad_rows(data: &[u8], bytes_per_row: u32, padded_bytes_per_row: u32) -> Vec<u8> {
+    data.chunks_exact(padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..bytes_per_row as usize])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpad_rows() {
+        assert_eq!(padded_bytes_per_row(12), 256);
+        assert_eq!(padded_bytes_per_row(512), 512);
+        let mut data = vec![0; 2 * 256];
+        data[..3].copy_from_slice(&[1, 2, 3]);
+        data[256..259].copy_from_slice(&[4, 5, 6]);
+        assert_eq!(unpad_rows(&data, 3, 256), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_read_rgba8() {
+        use crate::gpu::{adapter, device, instance};
+
+        let instance = instance();
+        let adapter = adapter(&instance, None).await.unwrap();
+        let (device, queue) = device(&adapter).await.unwrap();
+        let (width, height) = (3, 2);
+        let texels = (0..width * height * 4).map(|i| i as u8).collect::<Vec<_>>();
+        for format in [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Bgra8Unorm,
+        ] {
+            let size = wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            };
+            let desc = wgpu::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            };
+            let texture = device.create_texture(&desc);
+            let layout = wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            };
+            queue.write_texture(texture.as_image_copy(), &texels, layout, size);
+            let image = read_rgba8(&device, &queue, &texture).unwrap();
+            assert_eq!(image.dimensions(), (width, height));
+            let expected = match format {
+                wgpu::TextureFormat::Rgba8Unorm => [0, 1, 2, 3],
+                _ => [2, 1, 0, 3],
+            };
+            assert_eq!(image.get_pixel(0, 0).0, expected);
+            assert_eq!(image.get_pixel(2, 1).0[3], 23);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! offscreen color and depth textures to render into and sample afterwards
+
+use crate::{transform::DepthOrder, WndSize};
+
+use super::DepthBuffer;
+
+#[derive(Debug)]
+struct ColorTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+/// Color textures, one per format, with an optional [`DepthBuffer`] of the same size.
+///
+/// For mirrors, minimaps and post-processing.
+#[derive(Debug)]
+pub struct RenderTarget {
+    colors: Vec<ColorTexture>,
+    depth_buffer: Option<DepthBuffer>,
+    size: WndSize,
+    label: Option<String>,
+}
+impl RenderTarget {
+    /// `depth` is the depth order of the projection drawn with, or `None` for no depth buffer
+    pub fn new(
+        device: &wgpu::Device,
+        size: WndSize,
+        color_formats: &[wgpu::TextureFormat],
+        depth: Option<DepthOrder>,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        if color_formats.is_empty() {
+            anyhow::bail!("a render target needs at least one color format");
+        }
+        let max = device.limits().max_color_attachments as usize;
+        if max < color_formats.len() {
+            anyhow::bail!(
+                "{} color formats exceed the limit of {max}",
+                color_formats.len()
+            );
+        }
+        for format in color_formats {
+            if format.is_depth_stencil_format() || format.is_compressed() {
+                anyhow::bail!("{format:?} is not a color attachment format");
+            }
+        }
+        let mut this = Self {
+            colors: vec![],
+            depth_buffer: None,
+            size,
+            label: label.map(String::from),
+        };
+        this.colors = color_formats
+            .iter()
+            .map(|format| this.create_color(device, *format))
+            .collect();
+        this.depth_buffer = depth.map(|order| this.create_depth_buffer(device, order));
+        Ok(this)
+    }
+
+    /// recreates every texture at `size`, dropping their contents
+    pub fn resize(&mut self, device: &wgpu::Device, size: WndSize) {
+        self.size = size;
+        self.colors = self
+            .colors
+            .iter()
+            .map(|color| self.create_color(device, color.texture.format()))
+            .collect();
+        if let Some(depth_buffer) = &self.depth_buffer {
+            self.depth_buffer = Some(self.create_depth_buffer(device, depth_buffer.order()));
+        }
+    }
+    pub fn size(&self) -> WndSize {
+        self.size
+    }
+
+    /// number of color textures
+    pub fn color_count(&self) -> usize {
+        self.colors.len()
+    }
+    pub fn color_formats(&self) -> Vec<wgpu::TextureFormat> {
+        self.colors.iter().map(|c| c.texture.format()).collect()
+    }
+    /// also a copy source, e.g. for [`super::readback::read_rgba8`]
+    pub fn color_texture(&self, index: usize) -> &wgpu::Texture {
+        &self.colors[index].texture
+    }
+    pub fn color_view(&self, index: usize) -> &wgpu::TextureView {
+        &self.colors[index].view
+    }
+    /// a fresh view of a color texture, such as the one [`crate::DrawArgs`] takes
+    pub fn create_color_view(&self, index: usize) -> wgpu::TextureView {
+        let desc = wgpu::TextureViewDescriptor::default();
+        self.colors[index].texture.create_view(&desc)
+    }
+    /// for sampling a color texture once the passes drawing into it have ended
+    pub fn color_layout(&self, index: usize) -> wgpu::BindingType {
+        let format = self.colors[index].texture.format();
+        wgpu::BindingType::Texture {
+            sample_type: format.sample_type(None, None).expect("color format"),
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    }
+    /// one attachment per color texture, in format order
+    pub fn color_attachments(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> Vec<Option<wgpu::RenderPassColorAttachment<'_>>> {
+        self.colors
+            .iter()
+            .map(|color| {
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &color.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            })
+            .collect()
+    }
+    pub fn depth_buffer(&self) -> Option<&DepthBuffer> {
+        self.depth_buffer.as_ref()
+    }
+
+    fn create_color(&self, device: &wgpu::Device, format: wgpu::TextureFormat) -> ColorTexture {
+        let desc = wgpu::TextureDescriptor {
+            label: self.label.as_deref(),
+            size: wgpu::Extent3d {
+                width: self.size.width,
+                height: self.size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        };
+        let texture = device.create_texture(&desc);
+        let desc = wgpu::TextureViewDescriptor::default();
+        let view = texture.create_view(&desc);
+        ColorTexture { texture, view }
+    }
+    fn create_depth_buffer(&self, device: &wgpu::Device, order: DepthOrder) -> DepthBuffer {
+        DepthBuffer::new(device, self.size, order, self.label.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::texture::readback::read_rgba8;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render_target() {
+        use crate::gpu::{adapter, device, instance};
+
+        let instance = instance();
+        let adapter = adapter(&instance, None).await.unwrap();
+        let (device, queue) = device(&adapter).await.unwrap();
+        let size = WndSize {
+            width: 5,
+            height: 3,
+        };
+        let formats = [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Rgba16Float,
+        ];
+        let mut target = RenderTarget::new(
+            &device,
+            size,
+            &formats,
+            Some(DepthOrder::Reversed),
+            Some("target"),
+        )
+        .unwrap();
+        assert!(RenderTarget::new(&device, size, &[], None, None).is_err());
+        let depth = [wgpu::TextureFormat::Depth32Float];
+        assert!(RenderTarget::new(&device, size, &depth, None, None).is_err());
+
+        let size = WndSize {
+            width: 7,
+            height: 2,
+        };
+        target.resize(&device, size);
+        assert_eq!(target.color_texture(1).width(), 7);
+        assert_eq!(target.color_formats(), formats);
+        assert_eq!(target.depth_buffer().unwrap().order(), DepthOrder::Reversed);
+
+        let desc = wgpu::CommandEncoderDescriptor { label: None };
+        let mut command = device.create_command_encoder(&desc);
+        {
+            let color_attachments = target.color_attachments(wgpu::LoadOp::Clear(wgpu::Color::RED));
+            let desc = wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &color_attachments,
+                depth_stencil_attachment: target.depth_buffer().map(|d| d.attachment_clear()),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            };
+            let _ = command.begin_render_pass(&desc);
+        }
+        queue.submit([command.finish()]);
+        let image = read_rgba8(&device, &queue, target.color_texture(0)).unwrap();
+        assert_eq!(image.dimensions(), (7, 2));
+        assert_eq!(image.get_pixel(6, 1).0, [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,1316 @@
+use std::num::NonZeroUsize;
+
+use math::{
+    matrix::{ArrayMatrix, Size},
+    vector::{Vector, VectorMut},
+};
+use serde::{Deserialize, Serialize};
+
+pub use typed::{Mat4, Point3, Vec3};
+
+mod typed;
+
+pub type Point = [f64; 4];
+pub type PointMatrix = ArrayMatrix<f64, 4>;
+pub fn point_size() -> Size {
+    Size {
+        rows: NonZeroUsize::new(4).unwrap(),
+        cols: NonZeroUsize::new(1).unwrap(),
+    }
+}
+pub type TransformMatrix = ArrayMatrix<f64, 16>;
+pub fn transform_size() -> Size {
+    Size {
+        rows: NonZeroUsize::new(4).unwrap(),
+        cols: NonZeroUsize::new(4).unwrap(),
+    }
+}
+
+pub fn point(var: [f64; 3]) -> Point {
+    [var[0], var[1], var[2], 1.]
+}
+
+pub fn identity() -> TransformMatrix {
+    TransformMatrix::identity(transform_size().rows)
+}
+pub fn scale(var: [f64; 3]) -> TransformMatrix {
+    let data = [
+        var[0], 0., 0., 0., //
+        0., var[1], 0., 0., //
+        0., 0., var[2], 0., //
+        0., 0., 0., 1., //
+    ];
+    TransformMatrix::new(transform_size(), data)
+}
+pub fn translate(var: [f64; 3]) -> TransformMatrix {
+    let data = [
+        1., 0., 0., var[0], //
+        0., 1., 0., var[1], //
+        0., 0., 1., var[2], //
+        0., 0., 0., 1., //
+    ];
+    TransformMatrix::new(transform_size(), data)
+}
+pub fn rotate(mut axises: [f64; 3], angle: f64) -> TransformMatrix {
+    let cos = angle.cos();
+    let cos_com = 1. - angle.cos();
+    let sin = angle.sin();
+    axises.normalize();
+    let a = axises;
+    let data = [
+        // row
+        cos + (a[0].powi(2) * cos_com),
+        (a[0] * a[1] * cos_com) - (a[2] * sin),
+        (a[0] * a[2] * cos_com) + (a[1] * sin),
+        0.,
+        // row
+        (a[0] * a[1] * cos_com) + (a[2] * sin),
+        cos + (a[1].powi(2) * cos_com),
+        (a[1] * a[2] * cos_com) - (a[0] * sin),
+        0.,
+        // row
+        (a[0] * a[2] * cos_com) - (a[1] * sin),
+        (a[1] * a[2] * cos_com) + (a[0] * sin),
+        cos + (a[2].powi(2) * cos_com),
+        0.,
+        // row
+        0.,
+        0.,
+        0.,
+        1.,
+    ];
+    TransformMatrix::new(transform_size(), data)
+}
+pub fn change_of_space(i: [f64; 3], j: [f64; 3], k: [f64; 3], h: [f64; 3]) -> TransformMatrix {
+    let data = [
+        i[0], i[1], i[2], 0., //
+        j[0], j[1], j[2], 0., //
+        k[0], k[1], k[2], 0., //
+        0., 0., 0., 1., //
+    ];
+    let change_of_axises = ArrayMatrix::new(transform_size(), data);
+    let origin_translate = translate(h.map(|x| -x));
+    change_of_axises.mul_matrix_square(&origin_translate)
+}
+pub fn look_at(h: [f64; 3], target: [f64; 3], up: [f64; 3]) -> TransformMatrix {
+    let mut k: [f64; 3] = h;
+    k.sub(&target);
+    k.normalize();
+    let mut i = up.cross(&k);
+    i.normalize();
+    let j = k.cross(&i);
+    change_of_space(i, j, k, h)
+}
+pub fn orthographic(
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+    near: f64,
+    far: f64,
+    clip: ClipSpace,
+) -> TransformMatrix {
+    let forward = clip.handedness.forward();
+    let (depth_scale, depth_offset) = match clip.depth {
+        DepthRange::NegOneToOne => (2. / (far - near), -(far + near) / (far - near)),
+        DepthRange::ZeroToOne => (1. / (far - near), -near / (far - near)),
+    };
+    let data = [
+        // row
+        2. / (right - left),
+        0.,
+        0.,
+        -(right + left) / (right - left),
+        // row
+        0.,
+        2. / (top - bottom),
+        0.,
+        -(top + bottom) / (top - bottom),
+        // row
+        0.,
+        0.,
+        forward * depth_scale,
+        depth_offset,
+        // row
+        0.,
+        0.,
+        0.,
+        1.,
+    ];
+    TransformMatrix::new(transform_size(), data)
+}
+pub fn perspective(fov: f64, aspect: f64, near: f64, far: f64, clip: ClipSpace) -> TransformMatrix {
+    let (depth_scale, depth_offset) = match clip.depth {
+        DepthRange::NegOneToOne => (
+            (far + near) / (far - near),
+            -(2. * far * near) / (far - near),
+        ),
+        DepthRange::ZeroToOne => (far / (far - near), -(far * near) / (far - near)),
+    };
+    perspective_with_depth(fov, aspect, depth_scale, depth_offset, clip.handedness)
+}
+/// [`perspective`] with the far plane pushed to infinity
+pub fn perspective_infinite(fov: f64, aspect: f64, near: f64, clip: ClipSpace) -> TransformMatrix {
+    let depth_offset = match clip.depth {
+        DepthRange::NegOneToOne => -2. * near,
+        DepthRange::ZeroToOne => -near,
+    };
+    perspective_with_depth(fov, aspect, 1., depth_offset, clip.handedness)
+}
+fn perspective_with_depth(
+    fov: f64,
+    aspect: f64,
+    depth_scale: f64,
+    depth_offset: f64,
+    handedness: Handedness,
+) -> TransformMatrix {
+    let tan_inv = 1. / f64::tan(fov / 2.);
+    let forward = handedness.forward();
+    let data = [
+        // row
+        (1. / aspect) * tan_inv,
+        0.,
+        0.,
+        0.,
+        // row
+        0.,
+        tan_inv,
+        0.,
+        0.,
+        // row
+        0.,
+        0.,
+        forward * depth_scale,
+        depth_offset,
+        // row
+        0.,
+        0.,
+        forward,
+        0.,
+    ];
+    TransformMatrix::new(transform_size(), data)
+}
+/// flips clip depth so that far depth lands near zero, where floats are dense
+pub fn reverse_depth(range: DepthRange) -> TransformMatrix {
+    let w = match range {
+        DepthRange::NegOneToOne => 0.,
+        DepthRange::ZeroToOne => 1.,
+    };
+    let data = [
+        1., 0., 0., 0., //
+        0., 1., 0., 0., //
+        0., 0., -1., w, //
+        0., 0., 0., 1., //
+    ];
+    TransformMatrix::new(transform_size(), data)
+}
+
+/// `None` if the matrix is singular
+pub fn inverse(m: &TransformMatrix) -> Option<TransformMatrix> {
+    let m = m.clone().into_buffer();
+    let adj = adjugate(&m);
+    let det = m[0] * adj[0] + m[1] * adj[4] + m[2] * adj[8] + m[3] * adj[12];
+    let columns = [0, 1, 2, 3].map(|c| [m[c], m[4 + c], m[8 + c], m[12 + c]]);
+    if is_singular(det, columns) {
+        return None;
+    }
+    Some(TransformMatrix::new(transform_size(), adj.map(|x| x / det)))
+}
+pub fn determinant(m: &TransformMatrix) -> f64 {
+    let m = m.clone().into_buffer();
+    let adj = adjugate(&m);
+    m[0] * adj[0] + m[1] * adj[4] + m[2] * adj[8] + m[3] * adj[12]
+}
+/// [`inverse`] for matrices whose last row is `0 0 0 1`, such as any mix of
+/// [`translate`], [`rotate`] and [`scale`]
+pub fn inverse_affine(m: &TransformMatrix) -> Option<TransformMatrix> {
+    let m = m.clone().into_buffer();
+    let linear = [[m[0], m[1], m[2]], [m[4], m[5], m[6]], [m[8], m[9], m[10]]];
+    let inv = inverse_3x3(linear)?;
+    let t = [m[3], m[7], m[11]];
+    let mut data = [0.; 16];
+    for row in 0..3 {
+        data[row * 4..row * 4 + 3].copy_from_slice(&inv[row]);
+        data[row * 4 + 3] = -(0..3).map(|col| inv[row][col] * t[col]).sum::<f64>();
+    }
+    data[15] = 1.;
+    Some(TransformMatrix::new(transform_size(), data))
+}
+/// inverse-transpose of the upper 3x3, which keeps normals perpendicular under non-uniform scale
+pub fn normal_matrix(m: &TransformMatrix) -> Option<TransformMatrix> {
+    let m = m.clone().into_buffer();
+    let linear = [[m[0], m[1], m[2]], [m[4], m[5], m[6]], [m[8], m[9], m[10]]];
+    let inv = inverse_3x3(linear)?;
+    let data = [
+        inv[0][0], inv[1][0], inv[2][0], 0., //
+        inv[0][1], inv[1][1], inv[2][1], 0., //
+        inv[0][2], inv[1][2], inv[2][2], 0., //
+        0., 0., 0., 1., //
+    ];
+    Some(TransformMatrix::new(transform_size(), data))
+}
+fn inverse_3x3(m: [[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    // transposed cofactors
+    let adj = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let det = m[0][0] * adj[0][0] + m[0][1] * adj[1][0] + m[0][2] * adj[2][0];
+    let columns = [0, 1, 2].map(|c| [m[0][c], m[1][c], m[2][c]]);
+    if is_singular(det, columns) {
+        return None;
+    }
+    Some(adj.map(|row| row.map(|x| x / det)))
+}
+/// Whether a determinant is too small to divide by.
+///
+/// Relative to the product of the column lengths, which bounds it, so that tiny but
+/// well-conditioned scales still invert.
+fn is_singular<const N: usize>(det: f64, columns: [[f64; N]; N]) -> bool {
+    let bound = columns
+        .iter()
+        .map(|c| c.iter().map(|x| x * x).sum::<f64>().sqrt())
+        .product::<f64>();
+    det.abs() <= f64::EPSILON * bound
+}
+/// transposed cofactors of a row-major 4x4
+fn adjugate(m: &[f64; 16]) -> [f64; 16] {
+    let mut inv = [0.; 16];
+    inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+        + m[9] * m[7] * m[14]
+        + m[13] * m[6] * m[11]
+        - m[13] * m[7] * m[10];
+    inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+        - m[8] * m[7] * m[14]
+        - m[12] * m[6] * m[11]
+        + m[12] * m[7] * m[10];
+    inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+        + m[8] * m[7] * m[13]
+        + m[12] * m[5] * m[11]
+        - m[12] * m[7] * m[9];
+    inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+        - m[8] * m[6] * m[13]
+        - m[12] * m[5] * m[10]
+        + m[12] * m[6] * m[9];
+    inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+        - m[9] * m[3] * m[14]
+        - m[13] * m[2] * m[11]
+        + m[13] * m[3] * m[10];
+    inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+        + m[8] * m[3] * m[14]
+        + m[12] * m[2] * m[11]
+        - m[12] * m[3] * m[10];
+    inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+        - m[8] * m[3] * m[13]
+        - m[12] * m[1] * m[11]
+        + m[12] * m[3] * m[9];
+    inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+        + m[8] * m[2] * m[13]
+        + m[12] * m[1] * m[10]
+        - m[12] * m[2] * m[9];
+    inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+        + m[5] * m[3] * m[14]
+        + m[13] * m[2] * m[7]
+        - m[13] * m[3] * m[6];
+    inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+        - m[4] * m[3] * m[14]
+        - m[12] * m[2] * m[7]
+        + m[12] * m[3] * m[6];
+    inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+        + m[4] * m[3] * m[13]
+        + m[12] * m[1] * m[7]
+        - m[12] * m[3] * m[5];
+    inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+        - m[4] * m[2] * m[13]
+        - m[12] * m[1] * m[6]
+        + m[12] * m[2] * m[5];
+    inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+        - m[5] * m[3] * m[10]
+        - m[9] * m[2] * m[7]
+        + m[9] * m[3] * m[6];
+    inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+        + m[4] * m[3] * m[10]
+        + m[8] * m[2] * m[7]
+        - m[8] * m[3] * m[6];
+    inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+        - m[4] * m[3] * m[9]
+        - m[8] * m[1] * m[7]
+        + m[8] * m[3] * m[5];
+    inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+        + m[4] * m[2] * m[9]
+        + m[8] * m[1] * m[6]
+        - m[8] * m[2] * m[5];
+    inv
+}
+
+/// translation, rotation and scale that [`Trs::matrix`] composes in that order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Trs {
+    pub translation: [f64; 3],
+    pub rotation: Quaternion,
+    pub scale: [f64; 3],
+}
+impl Trs {
+    /// `None` if `m` has projection, shear or a zero scale
+    pub fn decompose(m: &TransformMatrix) -> Option<Self> {
+        let buf = m.clone().into_buffer();
+        if buf[12..] != [0., 0., 0., 1.] {
+            return None;
+        }
+        let column = |c: usize| [buf[c], buf[4 + c], buf[8 + c]];
+        let mut axes = [column(0), column(1), column(2)];
+        let mut scale = axes.map(|a| (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt());
+        if scale.iter().any(|s| *s < f64::EPSILON) {
+            return None;
+        }
+        // a mirror cannot be a rotation, so fold it into the scale
+        if determinant(m) < 0. {
+            scale[0] = -scale[0];
+        }
+        for (axis, scale) in axes.iter_mut().zip(scale) {
+            axis.mul(1. / scale);
+        }
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        let is_orthogonal = dot(axes[0], axes[1]).abs() < 1e-9
+            && dot(axes[1], axes[2]).abs() < 1e-9
+            && dot(axes[0], axes[2]).abs() < 1e-9;
+        if !is_orthogonal {
+            return None;
+        }
+        let data = [
+            axes[0][0], axes[1][0], axes[2][0], 0., //
+            axes[0][1], axes[1][1], axes[2][1], 0., //
+            axes[0][2], axes[1][2], axes[2][2], 0., //
+            0., 0., 0., 1., //
+        ];
+        Some(Self {
+            translation: [buf[3], buf[7], buf[11]],
+            rotation: Quaternion::from_matrix(&TransformMatrix::new(transform_size(), data)),
+            scale,
+        })
+    }
+    pub fn from_translation(translation: [f64; 3]) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+    pub fn matrix(&self) -> TransformMatrix {
+        translate(self.translation)
+            .mul_matrix_square(&self.rotation.to_matrix())
+            .mul_matrix_square(&scale(self.scale))
+    }
+}
+impl Default for Trs {
+    fn default() -> Self {
+        Self {
+            translation: [0.; 3],
+            rotation: Quaternion::IDENTITY,
+            scale: [1.; 3],
+        }
+    }
+}
+/// applies `m` to a point, dividing by the resulting `w`
+pub fn transform_point(m: &TransformMatrix, p: [f64; 3]) -> [f64; 3] {
+    let h = transform_homogeneous(m, [p[0], p[1], p[2], 1.]);
+    [h[0] / h[3], h[1] / h[3], h[2] / h[3]]
+}
+/// applies `m` to a direction, ignoring translation
+pub fn transform_vector(m: &TransformMatrix, v: [f64; 3]) -> [f64; 3] {
+    let h = transform_homogeneous(m, [v[0], v[1], v[2], 0.]);
+    [h[0], h[1], h[2]]
+}
+fn transform_homogeneous(m: &TransformMatrix, p: [f64; 4]) -> [f64; 4] {
+    let m = m.clone().into_buffer();
+    let mut out = [0.; 4];
+    for (row, out) in out.iter_mut().enumerate() {
+        *out = (0..4).map(|col| m[row * 4 + col] * p[col]).sum();
+    }
+    out
+}
+
+/// points at `origin + t * direction` for `t >= 0`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: [f64; 3],
+    pub direction: [f64; 3],
+}
+impl Ray {
+    pub fn at(&self, t: f64) -> [f64; 3] {
+        let mut p = self.direction;
+        p.mul(t);
+        p.add(&self.origin);
+        p
+    }
+    /// keeps the direction unnormalized so that `t` still measures the same point
+    pub fn transform(&self, m: &TransformMatrix) -> Self {
+        Self {
+            origin: transform_point(m, self.origin),
+            direction: transform_vector(m, self.direction),
+        }
+    }
+
+    /// nearest `t` at which the ray enters the box, or zero if it starts inside
+    pub fn intersect_aabb(&self, min: [f64; 3], max: [f64; 3]) -> Option<f64> {
+        let mut t_min: f64 = 0.;
+        let mut t_max = f64::INFINITY;
+        for i in 0..3 {
+            if self.direction[i] == 0. {
+                if self.origin[i] < min[i] || max[i] < self.origin[i] {
+                    return None;
+                }
+                continue;
+            }
+            let inv = 1. / self.direction[i];
+            let a = (min[i] - self.origin[i]) * inv;
+            let b = (max[i] - self.origin[i]) * inv;
+            t_min = t_min.max(a.min(b));
+            t_max = t_max.min(a.max(b));
+        }
+        (t_min <= t_max).then_some(t_min)
+    }
+    /// nearest `t` at which the ray enters the sphere, or zero if it starts inside
+    pub fn intersect_sphere(&self, center: [f64; 3], radius: f64) -> Option<f64> {
+        let mut offset = self.origin;
+        offset.sub(&center);
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        let a = dot(self.direction, self.direction);
+        let b = dot(offset, self.direction);
+        let c = dot(offset, offset) - radius * radius;
+        if c <= 0. {
+            return Some(0.);
+        }
+        let discriminant = b * b - a * c;
+        if discriminant < 0. || 0. <= b {
+            return None;
+        }
+        Some((-b - discriminant.sqrt()) / a)
+    }
+    /// Möller–Trumbore; hits from either side of the triangle
+    pub fn intersect_triangle(&self, triangle: [[f64; 3]; 3]) -> Option<f64> {
+        let [a, b, c] = triangle;
+        let mut ab = b;
+        ab.sub(&a);
+        let mut ac = c;
+        ac.sub(&a);
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        let p = self.direction.cross(&ac);
+        let det = dot(ab, p);
+        // the triple product of the edges and the direction
+        if is_singular(det, [ab, ac, self.direction]) {
+            return None;
+        }
+        let mut s = self.origin;
+        s.sub(&a);
+        let u = dot(s, p) / det;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+        let q = s.cross(&ab);
+        let v = dot(self.direction, q) / det;
+        if v < 0. || 1. < u + v {
+            return None;
+        }
+        let t = dot(ac, q) / det;
+        (0. <= t).then_some(t)
+    }
+}
+
+/// rotation as `w + xi + yj + zk`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+impl Quaternion {
+    pub const IDENTITY: Self = Self {
+        w: 1.,
+        x: 0.,
+        y: 0.,
+        z: 0.,
+    };
+    /// same handedness as [`rotate`]
+    pub fn from_axis_angle(mut axis: [f64; 3], angle: f64) -> Self {
+        axis.normalize();
+        let (sin, cos) = (angle / 2.).sin_cos();
+        Self {
+            w: cos,
+            x: axis[0] * sin,
+            y: axis[1] * sin,
+            z: axis[2] * sin,
+        }
+    }
+    pub fn conjugate(&self) -> Self {
+        Self {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+    /// counters drift from repeated multiplication
+    pub fn normalize(&self) -> Self {
+        let len = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        Self {
+            w: self.w / len,
+            x: self.x / len,
+            y: self.y / len,
+            z: self.z / len,
+        }
+    }
+    /// rotates a point about the origin
+    pub fn rotate(&self, v: [f64; 3]) -> [f64; 3] {
+        let p = Self {
+            w: 0.,
+            x: v[0],
+            y: v[1],
+            z: v[2],
+        };
+        let r = *self * p * self.conjugate();
+        [r.x, r.y, r.z]
+    }
+    /// angle in `[0, 2π]` about a unit axis
+    pub fn to_axis_angle(&self) -> ([f64; 3], f64) {
+        let q = self.normalize();
+        let angle = 2. * q.w.clamp(-1., 1.).acos();
+        let sin = (1. - q.w * q.w).max(0.).sqrt();
+        if sin < 1e-12 {
+            return ([1., 0., 0.], 0.);
+        }
+        ([q.x / sin, q.y / sin, q.z / sin], angle)
+    }
+    /// turns about the world X, then Y, then Z axis by the matching angle
+    pub fn from_euler(angles: [f64; 3]) -> Self {
+        let x = Self::from_axis_angle([1., 0., 0.], angles[0]);
+        let y = Self::from_axis_angle([0., 1., 0.], angles[1]);
+        let z = Self::from_axis_angle([0., 0., 1.], angles[2]);
+        z * y * x
+    }
+    /// inverse of [`Self::from_euler`] with the Y angle in `[-π/2, π/2]`
+    pub fn to_euler(&self) -> [f64; 3] {
+        let m = self.rotation_rows();
+        let sin_y = -m[2][0];
+        if 1. - 1e-9 < sin_y.abs() {
+            // gimbal lock; X and Z turn about the same axis so put it all on Z
+            let z = (-m[0][1]).atan2(m[1][1]);
+            return [0., std::f64::consts::FRAC_PI_2.copysign(sin_y), z];
+        }
+        [m[2][1].atan2(m[2][2]), sin_y.asin(), m[1][0].atan2(m[0][0])]
+    }
+    /// same matrix as [`rotate`] for the same axis and angle
+    pub fn to_matrix(&self) -> TransformMatrix {
+        let m = self.rotation_rows();
+        let data = [
+            m[0][0], m[0][1], m[0][2], 0., //
+            m[1][0], m[1][1], m[1][2], 0., //
+            m[2][0], m[2][1], m[2][2], 0., //
+            0., 0., 0., 1., //
+        ];
+        TransformMatrix::new(transform_size(), data)
+    }
+    /// reads the rotation out of the upper 3x3 of `m`, which must be orthonormal
+    pub fn from_matrix(m: &TransformMatrix) -> Self {
+        let m = m.clone().into_buffer();
+        let at = |row: usize, col: usize| m[row * 4 + col];
+        let trace = at(0, 0) + at(1, 1) + at(2, 2);
+        let q = if 0. < trace {
+            let s = (trace + 1.).sqrt() * 2.;
+            Self {
+                w: s / 4.,
+                x: (at(2, 1) - at(1, 2)) / s,
+                y: (at(0, 2) - at(2, 0)) / s,
+                z: (at(1, 0) - at(0, 1)) / s,
+            }
+        } else if at(1, 1) < at(0, 0) && at(2, 2) < at(0, 0) {
+            let s = (1. + at(0, 0) - at(1, 1) - at(2, 2)).sqrt() * 2.;
+            Self {
+                w: (at(2, 1) - at(1, 2)) / s,
+                x: s / 4.,
+                y: (at(0, 1) + at(1, 0)) / s,
+                z: (at(0, 2) + at(2, 0)) / s,
+            }
+        } else if at(2, 2) < at(1, 1) {
+            let s = (1. + at(1, 1) - at(0, 0) - at(2, 2)).sqrt() * 2.;
+            Self {
+                w: (at(0, 2) - at(2, 0)) / s,
+                x: (at(0, 1) + at(1, 0)) / s,
+                y: s / 4.,
+                z: (at(1, 2) + at(2, 1)) / s,
+            }
+        } else {
+            let s = (1. + at(2, 2) - at(0, 0) - at(1, 1)).sqrt() * 2.;
+            Self {
+                w: (at(1, 0) - at(0, 1)) / s,
+                x: (at(0, 2) + at(2, 0)) / s,
+                y: (at(1, 2) + at(2, 1)) / s,
+                z: s / 4.,
+            }
+        };
+        q.normalize()
+    }
+    fn rotation_rows(&self) -> [[f64; 3]; 3] {
+        let Self { w, x, y, z } = self.normalize();
+        [
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+            ],
+            [
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+            ],
+            [
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+            ],
+        ]
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    /// constant angular speed along the shorter arc
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let (other, cos) = self.nearer(other);
+        if 1. - 1e-9 < cos {
+            // too close for the sine to be divided by
+            return self.nlerp(&other, t);
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1. - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        self.scale(a).add(&other.scale(b)).normalize()
+    }
+    /// cheaper than [`Self::slerp`] but speeds up in the middle
+    pub fn nlerp(&self, other: &Self, t: f64) -> Self {
+        let (other, _) = self.nearer(other);
+        self.scale(1. - t).add(&other.scale(t)).normalize()
+    }
+    /// `other` or its negation, whichever is the same rotation by the shorter arc
+    fn nearer(&self, other: &Self) -> (Self, f64) {
+        let cos = self.dot(other);
+        if cos < 0. {
+            (other.scale(-1.), -cos)
+        } else {
+            (*other, cos)
+        }
+    }
+    fn scale(&self, v: f64) -> Self {
+        Self {
+            w: self.w * v,
+            x: self.x * v,
+            y: self.y * v,
+            z: self.z * v,
+        }
+    }
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            w: self.w + other.w,
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+/// applies `rhs` first
+impl core::ops::Mul for Quaternion {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self, rhs);
+        Self {
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipSpace {
+    pub depth: DepthRange,
+    pub handedness: Handedness,
+}
+impl ClipSpace {
+    pub const OPENGL: Self = Self {
+        depth: DepthRange::NegOneToOne,
+        handedness: Handedness::Right,
+    };
+    pub const WGPU: Self = Self {
+        depth: DepthRange::ZeroToOne,
+        handedness: Handedness::Right,
+    };
+}
+/// range of NDC depth between the near and far planes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthRange {
+    NegOneToOne,
+    ZeroToOne,
+}
+/// handedness of view space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Handedness {
+    /// camera looks down -Z
+    Right,
+    /// camera looks down +Z
+    Left,
+}
+impl Handedness {
+    fn forward(&self) -> f64 {
+        match self {
+            Handedness::Right => -1.,
+            Handedness::Left => 1.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthOrder {
+    /// depth grows with distance
+    Standard,
+    /// depth shrinks with distance
+    Reversed,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use math::matrix::Matrix;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_transform() {
+        let trans = translate([1., 2., 3.]);
+        let scale = scale([2., 2., 2.]);
+        let m = trans.mul_matrix_square(&scale);
+        let expected = TransformMatrix::new(
+            transform_size(),
+            [
+                2., 0., 0., 1., //
+                0., 2., 0., 2., //
+                0., 0., 2., 3., //
+                0., 0., 0., 1., //
+            ],
+        );
+        assert!(m.closes_to(&expected));
+
+        let point = point([1., 2., 3.]);
+        let mut p: PointMatrix = point.into_matrix().unwrap();
+        m.mul_matrix_in(&p.clone(), &mut p);
+        dbg!(&p);
+        assert!(p.closes_to(&ArrayMatrix::new(point_size(), [3., 6., 9., 1.])));
+        Point::try_from(p).unwrap();
+    }
+
+    fn ndc(m: &TransformMatrix, var: [f64; 3]) -> [f64; 3] {
+        let mut p: PointMatrix = point(var).into_matrix().unwrap();
+        m.mul_matrix_in(&p.clone(), &mut p);
+        let p = Point::try_from(p).unwrap();
+        [p[0] / p[3], p[1] / p[3], p[2] / p[3]]
+    }
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_perspective_clip_space() {
+        let fov = std::f64::consts::PI / 2.;
+        let (near, far) = (1., 10.);
+        let m = perspective(fov, 2., near, far, ClipSpace::WGPU);
+        assert_close(ndc(&m, [0., 0., -near]), [0., 0., 0.]);
+        assert_close(ndc(&m, [0., 0., -far]), [0., 0., 1.]);
+        assert_close(ndc(&m, [2., 1., -near]), [1., 1., 0.]);
+        assert_close(
+            ndc(&m, [-10., -2.5, -5.]),
+            [-1., -0.5, ndc(&m, [0., 0., -5.])[2]],
+        );
+
+        let m = perspective(fov, 2., near, far, ClipSpace::OPENGL);
+        assert_close(ndc(&m, [0., 0., -near]), [0., 0., -1.]);
+        assert_close(ndc(&m, [0., 0., -far]), [0., 0., 1.]);
+
+        let clip = ClipSpace {
+            depth: DepthRange::ZeroToOne,
+            handedness: Handedness::Left,
+        };
+        let m = perspective(fov, 2., near, far, clip);
+        assert_close(ndc(&m, [0., 0., near]), [0., 0., 0.]);
+        assert_close(ndc(&m, [0., 0., far]), [0., 0., 1.]);
+        assert_close(ndc(&m, [2., 1., near]), [1., 1., 0.]);
+
+        let m = perspective_infinite(fov, 1., near, ClipSpace::WGPU);
+        assert_close(ndc(&m, [0., 0., -near]), [0., 0., 0.]);
+        assert!(1. - ndc(&m, [0., 0., -1e12])[2] < 1e-9);
+    }
+
+    #[test]
+    fn test_orthographic_clip_space() {
+        let m = orthographic(-2., 2., 1., -1., 1., 3., ClipSpace::WGPU);
+        assert_close(ndc(&m, [-2., -1., -1.]), [-1., -1., 0.]);
+        assert_close(ndc(&m, [2., 1., -3.]), [1., 1., 1.]);
+
+        let m = orthographic(0., 4., 2., 0., 1., 3., ClipSpace::OPENGL);
+        assert_close(ndc(&m, [2., 1., -2.]), [0., 0., 0.]);
+        assert_close(ndc(&m, [0., 0., -1.]), [-1., -1., -1.]);
+
+        let clip = ClipSpace {
+            depth: DepthRange::NegOneToOne,
+            handedness: Handedness::Left,
+        };
+        let m = orthographic(-2., 2., 1., -1., 1., 3., clip);
+        assert_close(ndc(&m, [0., 0., 1.]), [0., 0., -1.]);
+        assert_close(ndc(&m, [0., 0., 3.]), [0., 0., 1.]);
+    }
+
+    /// the offsets once sat in the bottom row, which turned them into a perspective divide
+    #[test]
+    fn test_orthographic_translation() {
+        let m = orthographic(1., 5., 4., 2., 1., 3., ClipSpace::WGPU).into_buffer();
+        assert_eq!(m[12..], [0., 0., 0., 1.]);
+        assert_eq!([m[3], m[7]], [-1.5, -3.]);
+        assert_eq!(m[11], -0.5);
+
+        let m = orthographic(1., 5., 4., 2., 1., 3., ClipSpace::WGPU);
+        for p in [[1., 2., -1.], [5., 4., -3.], [3., 3., -2.]] {
+            let mut h: PointMatrix = point(p).into_matrix().unwrap();
+            m.mul_matrix_in(&h.clone(), &mut h);
+            assert_eq!(Point::try_from(h).unwrap()[3], 1.);
+        }
+        assert_close(ndc(&m, [1., 2., -1.]), [-1., -1., 0.]);
+        assert_close(ndc(&m, [5., 4., -3.]), [1., 1., 1.]);
+    }
+
+    #[test]
+    fn test_reverse_depth() {
+        let (near, far) = (1., 10.);
+        let m = perspective(1., 1., near, far, ClipSpace::WGPU);
+        let m = reverse_depth(DepthRange::ZeroToOne).mul_matrix_square(&m);
+        assert_close(ndc(&m, [0., 0., -near]), [0., 0., 1.]);
+        assert_close(ndc(&m, [0., 0., -far]), [0., 0., 0.]);
+
+        let m = perspective(1., 1., near, far, ClipSpace::OPENGL);
+        let m = reverse_depth(DepthRange::NegOneToOne).mul_matrix_square(&m);
+        assert_close(ndc(&m, [0., 0., -near]), [0., 0., 1.]);
+        assert_close(ndc(&m, [0., 0., -far]), [0., 0., -1.]);
+    }
+
+    fn trs(t: [f64; 3], axis: [f64; 3], angle: f64, s: [f64; 3]) -> TransformMatrix {
+        translate(t)
+            .mul_matrix_square(&rotate(axis, angle))
+            .mul_matrix_square(&scale(s))
+    }
+    fn any_scale() -> impl Strategy<Value = [f64; 3]> {
+        prop::array::uniform3(0.1_f64..5.).prop_flat_map(|s| {
+            prop::array::uniform3(any::<bool>())
+                .prop_map(move |flip| [0, 1, 2].map(|i| if flip[i] { -s[i] } else { s[i] }))
+        })
+    }
+
+    #[test]
+    fn test_inverse() {
+        let m = trs([1., -2., 3.], [1., 0.3, 0.5], 0.7, [2., 0.5, 4.]);
+        let inv = inverse(&m).unwrap();
+        assert!(m.mul_matrix_square(&inv).closes_to(&identity()));
+        assert!(inverse(&scale([1., 0., 1.])).is_none());
+        assert!(inverse_affine(&scale([1., 0., 1.])).is_none());
+        assert!(normal_matrix(&scale([1., 1., 0.])).is_none());
+        assert_eq!(determinant(&scale([1., 0., 1.])), 0.);
+
+        let p = [0.3, -4., 5.];
+        assert_close(transform_point(&inv, transform_point(&m, p)), p);
+
+        // projections are not affine but still invertible in general
+        let m = perspective(1., 1.5, 0.1, 100., ClipSpace::WGPU);
+        assert_matrix_close(&inverse(&m).unwrap().mul_matrix_square(&m), &identity());
+
+        // tiny but well-conditioned scales still invert, translation included
+        let tiny = translate([1., 0., -3.]).mul_matrix_square(&scale([1e-6; 3]));
+        let inv = inverse(&tiny).unwrap();
+        assert_matrix_close(&inv.mul_matrix_square(&tiny), &identity());
+        assert!(inverse_affine(&tiny).is_some());
+        assert_close(transform_point(&inv, [1., 1e-6, -3.]), [0., 1., 0.]);
+        assert!(normal_matrix(&scale([1e-6; 3])).is_some());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_inverse(
+            t in any_point(),
+            axis in axis(),
+            angle in angle(),
+            s in any_scale(),
+            p in any_point(),
+        ) {
+            let m = trs(t, axis, angle, s);
+            let general = inverse(&m).unwrap();
+            let affine = inverse_affine(&m).unwrap();
+            assert_matrix_close(&general, &affine);
+            assert_close(transform_point(&affine, transform_point(&m, p)), p);
+            let expected = scale(s.map(|x| 1. / x))
+                .mul_matrix_square(&rotate(axis, -angle))
+                .mul_matrix_square(&translate(t.map(|x| -x)));
+            assert_matrix_close(&affine, &expected);
+        }
+
+        #[test]
+        fn prop_determinant(
+            t in any_point(),
+            axis in axis(),
+            angle in angle(),
+            s in any_scale(),
+        ) {
+            let det = determinant(&trs(t, axis, angle, s));
+            prop_assert!((det - s[0] * s[1] * s[2]).abs() < 1e-9);
+            prop_assert!((determinant(&rotate(axis, angle)) - 1.).abs() < 1e-9);
+        }
+
+        #[test]
+        fn prop_normal_matrix(
+            t in any_point(),
+            axis in axis(),
+            angle in angle(),
+            s in any_scale(),
+            a in any_point(),
+            b in any_point(),
+        ) {
+            let m = trs(t, axis, angle, s);
+            let n = normal_matrix(&m).unwrap();
+            // the normal of a plane through two directions stays perpendicular to both
+            let normal = transform_vector(&n, a.cross(&b));
+            let dot = |x: [f64; 3], y: [f64; 3]| x[0] * y[0] + x[1] * y[1] + x[2] * y[2];
+            for direction in [a, b] {
+                let direction = transform_vector(&m, direction);
+                let len = (dot(normal, normal) * dot(direction, direction)).sqrt();
+                prop_assert!(dot(normal, direction).abs() <= 1e-9 * len);
+            }
+        }
+
+        #[test]
+        fn prop_decompose(
+            t in any_point(),
+            axis in axis(),
+            angle in angle(),
+            s in any_scale(),
+            p in any_point(),
+        ) {
+            let m = trs(t, axis, angle, s);
+            let trs = Trs::decompose(&m).unwrap();
+            assert_close(trs.translation, t);
+            assert_close(trs.scale.map(f64::abs), s.map(f64::abs));
+            assert_matrix_close(&trs.matrix(), &m);
+            assert_close(transform_point(&trs.matrix(), p), transform_point(&m, p));
+        }
+    }
+
+    #[test]
+    fn test_decompose_rejects() {
+        let shear = TransformMatrix::new(
+            transform_size(),
+            [
+                1., 1., 0., 0., //
+                0., 1., 0., 0., //
+                0., 0., 1., 0., //
+                0., 0., 0., 1., //
+            ],
+        );
+        assert!(Trs::decompose(&shear).is_none());
+        assert!(Trs::decompose(&scale([1., 0., 1.])).is_none());
+        let projection = perspective(1., 1., 0.1, 10., ClipSpace::WGPU);
+        assert!(Trs::decompose(&projection).is_none());
+    }
+
+    #[test]
+    fn test_ray_intersections() {
+        let ray = Ray {
+            origin: [0., 0., 5.],
+            direction: [0., 0., -1.],
+        };
+        assert_eq!(ray.intersect_aabb([-1., -1., -1.], [1., 1., 1.]), Some(4.));
+        // grazing an edge
+        assert_eq!(ray.intersect_aabb([0., 0., -1.], [1., 1., 1.]), Some(4.));
+        assert_eq!(ray.intersect_aabb([0.001, -1., -1.], [1., 1., 1.]), None);
+        // behind the origin
+        assert_eq!(ray.intersect_aabb([-1., -1., 6.], [1., 1., 7.]), None);
+        assert_eq!(ray.intersect_aabb([-1., -1., 4.], [1., 1., 6.]), Some(0.));
+
+        assert_eq!(ray.intersect_sphere([0., 0., 0.], 1.), Some(4.));
+        assert_eq!(ray.intersect_sphere([1., 0., 0.], 1.), Some(5.));
+        assert_eq!(ray.intersect_sphere([1.001, 0., 0.], 1.), None);
+        assert_eq!(ray.intersect_sphere([0., 0., 7.], 1.), None);
+        assert_eq!(ray.intersect_sphere([0., 0., 5.5], 1.), Some(0.));
+
+        let triangle = [[-1., -1., 2.], [1., -1., 2.], [0., 1., 2.]];
+        assert_eq!(ray.intersect_triangle(triangle), Some(3.));
+        let mut back_facing = triangle;
+        back_facing.swap(0, 1);
+        assert_eq!(ray.intersect_triangle(back_facing), Some(3.));
+        let beside = triangle.map(|[x, y, z]| [x + 1.001, y, z]);
+        assert_eq!(ray.intersect_triangle(beside), None);
+        let behind = triangle.map(|[x, y, z]| [x, y, z + 4.]);
+        assert_eq!(ray.intersect_triangle(behind), None);
+
+        let m = translate([0., 0., -4.]).mul_matrix_square(&scale([2., 2., 2.]));
+        let local = ray.transform(&inverse(&m).unwrap());
+        let t = local
+            .intersect_aabb([-0.5, -0.5, -0.5], [0.5, 0.5, 0.5])
+            .unwrap();
+        assert_close(ray.at(t), [0., 0., -3.]);
+    }
+
+    fn assert_matrix_close(a: &TransformMatrix, b: &TransformMatrix) {
+        let (a, b) = (a.clone().into_buffer(), b.clone().into_buffer());
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9),
+            "{a:?} != {b:?}"
+        );
+    }
+    fn axis() -> impl Strategy<Value = [f64; 3]> {
+        prop::array::uniform3(-1_f64..1.).prop_filter("degenerate axis", |a| {
+            0.1 < (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt()
+        })
+    }
+    fn angle() -> impl Strategy<Value = f64> {
+        -PI..PI
+    }
+    fn any_point() -> impl Strategy<Value = [f64; 3]> {
+        prop::array::uniform3(-10_f64..10.)
+    }
+    /// angle of the rotation taking `a` to `b`
+    fn angle_between(a: &Quaternion, b: &Quaternion) -> f64 {
+        2. * a.dot(b).abs().min(1.).acos()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_quaternion_matches_rotate(axis in axis(), angle in angle(), p in any_point()) {
+            let q = Quaternion::from_axis_angle(axis, angle);
+            let m = rotate(axis, angle);
+            assert_close(q.rotate(p), transform_vector(&m, p));
+            assert_matrix_close(&q.to_matrix(), &m);
+            assert_close(Quaternion::from_matrix(&m).rotate(p), q.rotate(p));
+        }
+
+        #[test]
+        fn prop_quaternion_composition(
+            a in axis(),
+            angle_a in angle(),
+            b in axis(),
+            angle_b in angle(),
+            p in any_point(),
+        ) {
+            let q = Quaternion::from_axis_angle(a, angle_a) * Quaternion::from_axis_angle(b, angle_b);
+            let m = rotate(a, angle_a).mul_matrix_square(&rotate(b, angle_b));
+            assert_close(q.rotate(p), transform_vector(&m, p));
+            assert_close(q.conjugate().rotate(q.rotate(p)), p);
+            let drifted = Quaternion { w: q.w * 3., ..q };
+            let len = drifted.normalize().dot(&drifted.normalize());
+            prop_assert!((len - 1.).abs() < 1e-9);
+        }
+
+        #[test]
+        fn prop_axis_angle_round_trip(mut axis in axis(), angle in 0.01..PI - 0.01) {
+            let (back_axis, back_angle) = Quaternion::from_axis_angle(axis, angle).to_axis_angle();
+            axis.normalize();
+            assert_close(back_axis, axis);
+            prop_assert!((back_angle - angle).abs() < 1e-9);
+        }
+
+        #[test]
+        fn prop_euler_round_trip(
+            x in -3.1_f64..3.1,
+            y in -1.5_f64..1.5,
+            z in -3.1_f64..3.1,
+            p in any_point(),
+        ) {
+            let q = Quaternion::from_euler([x, y, z]);
+            assert_close(q.to_euler(), [x, y, z]);
+            let m = rotate([0., 0., 1.], z)
+                .mul_matrix_square(&rotate([0., 1., 0.], y))
+                .mul_matrix_square(&rotate([1., 0., 0.], x));
+            assert_close(q.rotate(p), transform_vector(&m, p));
+        }
+
+        #[test]
+        fn prop_interpolation(
+            a in axis(),
+            angle_a in angle(),
+            b in axis(),
+            angle_b in angle(),
+            t in 0_f64..1.,
+            p in any_point(),
+        ) {
+            let qa = Quaternion::from_axis_angle(a, angle_a);
+            let qb = Quaternion::from_axis_angle(b, angle_b);
+            assert_close(qa.slerp(&qb, 0.).rotate(p), qa.rotate(p));
+            assert_close(qa.slerp(&qb, 1.).rotate(p), qb.rotate(p));
+            assert_close(qa.nlerp(&qb, 1.).rotate(p), qb.rotate(p));
+
+            // constant angular speed along the shorter arc
+            let total = angle_between(&qa, &qb);
+            prop_assert!(total <= PI + 1e-9);
+            let s = qa.slerp(&qb, t);
+            prop_assert!((angle_between(&qa, &s) - t * total).abs() < 1e-6);
+            prop_assert!((angle_between(&s, &qb) - (1. - t) * total).abs() < 1e-6);
+            let n = qa.nlerp(&qb, t);
+            prop_assert!((n.dot(&n) - 1.).abs() < 1e-9);
+            prop_assert!(angle_between(&qa, &n) <= total + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_quaternion_rotation() {
+        let axis = [1., 0.3, 0.5];
+        let q = Quaternion::from_axis_angle(axis, 0.7);
+        let v = [0.3, -4., 5.];
+        assert_close(q.rotate(v), transform_vector(&rotate(axis, 0.7), v));
+
+        let p = Quaternion::from_axis_angle([0., 1., 0.], -1.2);
+        let both = transform_vector(
+            &rotate([0., 1., 0.], -1.2).mul_matrix_square(&rotate(axis, 0.7)),
+            v,
+        );
+        assert_close((p * q).rotate(v), both);
+        assert_close((p * q).conjugate().rotate(both), v);
+    }
+
+    #[test]
+    fn test_gimbal_lock() {
+        let q = Quaternion::from_euler([0.3, PI / 2., 0.5]);
+        let back = Quaternion::from_euler(q.to_euler());
+        let p = [1., 2., 3.];
+        assert_close(back.rotate(p), q.rotate(p));
+    }
+
+    fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+    fn any_clip_space() -> impl Strategy<Value = ClipSpace> {
+        let depth = prop_oneof![Just(DepthRange::NegOneToOne), Just(DepthRange::ZeroToOne)];
+        let handedness = prop_oneof![Just(Handedness::Right), Just(Handedness::Left)];
+        (depth, handedness).prop_map(|(depth, handedness)| ClipSpace { depth, handedness })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_rotation_is_orthonormal(axis in axis(), angle in angle()) {
+            let m = rotate(axis, angle).into_buffer();
+            let row = |r: usize| [m[r * 4], m[r * 4 + 1], m[r * 4 + 2]];
+            for a in 0..3 {
+                for b in 0..3 {
+                    let expected = if a == b { 1. } else { 0. };
+                    prop_assert!((dot(row(a), row(b)) - expected).abs() < 1e-9);
+                }
+            }
+            prop_assert!((determinant(&rotate(axis, angle)) - 1.).abs() < 1e-9);
+            assert_close(transform_vector(&rotate(axis, angle), axis), axis);
+        }
+
+        #[test]
+        fn prop_rotation_is_counterclockwise(angle in angle()) {
+            // looking down +Z at the XY plane, positive angles turn X towards Y
+            let x = transform_vector(&rotate([0., 0., 1.], angle), [1., 0., 0.]);
+            assert_close(x, [angle.cos(), angle.sin(), 0.]);
+        }
+
+        #[test]
+        fn prop_look_at(eye in any_point(), target in any_point(), up in axis()) {
+            let mut forward = target;
+            forward.sub(&eye);
+            let distance = dot(forward, forward).sqrt();
+            prop_assume!(0.1 < distance);
+            // `up` must not be parallel to the view direction
+            let side = forward.cross(&up);
+            prop_assume!(0.1 < (dot(side, side) / dot(up, up)).sqrt() / distance);
+            let view = look_at(eye, target, up);
+            assert_close(transform_point(&view, eye), [0.; 3]);
+            assert_close(transform_point(&view, target), [0., 0., -distance]);
+            let mut above = eye;
+            above.add(&up);
+            let above = transform_point(&view, above);
+            prop_assert!(above[0].abs() < 1e-9);
+            prop_assert!(0. < above[1]);
+            prop_assert!((determinant(&view) - 1.).abs() < 1e-9);
+        }
+
+        #[test]
+        fn prop_perspective_depth(
+            fov in 0.1..PI - 0.1,
+            aspect in 0.2_f64..5.,
+            near in 0.01_f64..10.,
+            depth in 1.1_f64..1000.,
+            clip in any_clip_space(),
+        ) {
+            let far = near * depth;
+            let m = perspective(fov, aspect, near, far, clip);
+            let at = |distance: f64| ndc(&m, [0., 0., clip.handedness.forward() * distance])[2];
+            let near_depth = match clip.depth {
+                DepthRange::NegOneToOne => -1.,
+                DepthRange::ZeroToOne => 0.,
+            };
+            prop_assert!((at(near) - near_depth).abs() < 1e-9);
+            prop_assert!((at(far) - 1.).abs() < 1e-9);
+            let mid = at((near + far) / 2.);
+            prop_assert!(near_depth < mid && mid < 1.);
+            // the edge of the vertical field of view lands on the top of NDC
+            let half = (fov / 2.).tan() * far;
+            let top = ndc(&m, [0., half, clip.handedness.forward() * far]);
+            prop_assert!((top[1] - 1.).abs() < 1e-9);
+        }
+
+        #[test]
+        fn prop_change_of_space_inverts(
+            axis in axis(),
+            angle in angle(),
+            origin in any_point(),
+            p in any_point(),
+        ) {
+            let basis = rotate(axis, angle).into_buffer();
+            let column = |c: usize| [basis[c], basis[4 + c], basis[8 + c]];
+            let (i, j, k) = (column(0), column(1), column(2));
+            let to_local = change_of_space(i, j, k, origin);
+            // a point at `p` in the new space sits at `origin + p.x i + p.y j + p.z k`
+            let mut world = origin;
+            for (axis, x) in [i, j, k].into_iter().zip(p) {
+                let mut axis = axis;
+                axis.mul(x);
+                world.add(&axis);
+            }
+            assert_close(transform_point(&to_local, world), p);
+            let to_world = translate(origin).mul_matrix_square(&rotate(axis, angle));
+            assert_matrix_close(&to_local.mul_matrix_square(&to_world), &identity());
+            assert_matrix_close(&inverse_affine(&to_local).unwrap(), &to_world);
+        }
+    }
+}
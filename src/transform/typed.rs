@@ -0,0 +1,289 @@
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    identity, inverse, inverse_affine, rotate, scale, transform_point, transform_vector, translate,
+    Quaternion, TransformMatrix,
+};
+
+/// direction or offset; unaffected by translation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+impl Vec3 {
+    pub const ZERO: Self = Self::new(0., 0., 0.);
+    pub const X: Self = Self::new(1., 0., 0.);
+    pub const Y: Self = Self::new(0., 1., 0.);
+    pub const Z: Self = Self::new(0., 0., 1.);
+
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+    pub fn dot(self, rhs: Self) -> f64 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+    pub fn cross(self, rhs: Self) -> Self {
+        Self::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+    /// `None` for the zero vector
+    pub fn normalize(self) -> Option<Self> {
+        let len = self.length();
+        (f64::EPSILON < len).then(|| self / len)
+    }
+    pub fn to_array(self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+impl From<[f64; 3]> for Vec3 {
+    fn from(v: [f64; 3]) -> Self {
+        Self::new(v[0], v[1], v[2])
+    }
+}
+impl From<Vec3> for [f64; 3] {
+    fn from(v: Vec3) -> Self {
+        v.to_array()
+    }
+}
+impl Add for Vec3 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl Sub for Vec3 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+impl Neg for Vec3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+impl Mul<f64> for Vec3 {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+impl Mul<Vec3> for f64 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        rhs * self
+    }
+}
+impl Div<f64> for Vec3 {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self {
+        self * (1. / rhs)
+    }
+}
+
+/// position; only offsets can be added to it, and two of them subtract into an offset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Point3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+impl Point3 {
+    pub const ORIGIN: Self = Self::new(0., 0., 0.);
+
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+    /// offset from the origin
+    pub fn to_vec(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+    pub fn to_array(self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+impl From<[f64; 3]> for Point3 {
+    fn from(v: [f64; 3]) -> Self {
+        Self::new(v[0], v[1], v[2])
+    }
+}
+impl From<Point3> for [f64; 3] {
+    fn from(v: Point3) -> Self {
+        v.to_array()
+    }
+}
+impl Add<Vec3> for Point3 {
+    type Output = Self;
+    fn add(self, rhs: Vec3) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+impl AddAssign<Vec3> for Point3 {
+    fn add_assign(&mut self, rhs: Vec3) {
+        *self = *self + rhs;
+    }
+}
+impl Sub<Vec3> for Point3 {
+    type Output = Self;
+    fn sub(self, rhs: Vec3) -> Self {
+        self + -rhs
+    }
+}
+impl SubAssign<Vec3> for Point3 {
+    fn sub_assign(&mut self, rhs: Vec3) {
+        *self = *self - rhs;
+    }
+}
+impl Sub for Point3 {
+    type Output = Vec3;
+    fn sub(self, rhs: Self) -> Vec3 {
+        Vec3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+/// 4x4 transform; `a * b` applies `b` first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mat4(TransformMatrix);
+impl Mat4 {
+    pub fn identity() -> Self {
+        Self(identity())
+    }
+    pub fn translation(v: Vec3) -> Self {
+        Self(translate(v.to_array()))
+    }
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        Self(rotate(axis.to_array(), angle))
+    }
+    pub fn scaling(v: Vec3) -> Self {
+        Self(scale(v.to_array()))
+    }
+    pub fn matrix(&self) -> &TransformMatrix {
+        &self.0
+    }
+    pub fn into_matrix(self) -> TransformMatrix {
+        self.0
+    }
+    /// `None` if singular
+    pub fn inverse(&self) -> Option<Self> {
+        inverse(&self.0).map(Self)
+    }
+    /// [`Self::inverse`] for matrices without projection
+    pub fn inverse_affine(&self) -> Option<Self> {
+        inverse_affine(&self.0).map(Self)
+    }
+    /// column-major, as WGSL lays out `mat4x4<f32>`
+    pub fn to_cols_f32(&self) -> [[f32; 4]; 4] {
+        let m = self.0.clone().into_buffer();
+        let mut cols = [[0.; 4]; 4];
+        for (c, col) in cols.iter_mut().enumerate() {
+            for (r, x) in col.iter_mut().enumerate() {
+                *x = m[r * 4 + c] as f32;
+            }
+        }
+        cols
+    }
+}
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+impl From<TransformMatrix> for Mat4 {
+    fn from(m: TransformMatrix) -> Self {
+        Self(m)
+    }
+}
+impl From<Quaternion> for Mat4 {
+    fn from(q: Quaternion) -> Self {
+        Self(q.to_matrix())
+    }
+}
+impl Mul for &Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: Self) -> Mat4 {
+        Mat4(self.0.mul_matrix_square(&rhs.0))
+    }
+}
+impl Mul for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        &self * &rhs
+    }
+}
+/// divides by the resulting `w`, so it also projects
+impl Mul<Point3> for &Mat4 {
+    type Output = Point3;
+    fn mul(self, rhs: Point3) -> Point3 {
+        transform_point(&self.0, rhs.to_array()).into()
+    }
+}
+impl Mul<Point3> for Mat4 {
+    type Output = Point3;
+    fn mul(self, rhs: Point3) -> Point3 {
+        &self * rhs
+    }
+}
+/// ignores translation
+impl Mul<Vec3> for &Mat4 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        transform_vector(&self.0, rhs.to_array()).into()
+    }
+}
+impl Mul<Vec3> for Mat4 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        &self * rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed() {
+        let m = Mat4::translation(Vec3::new(1., 2., 3.)) * Mat4::scaling(Vec3::new(2., 2., 2.));
+        let p = &m * Point3::new(1., 0., -1.);
+        assert_eq!(p, Point3::new(3., 2., 1.));
+        let v = &m * Vec3::new(1., 0., -1.);
+        assert_eq!(v, Vec3::new(2., 0., -2.));
+        assert_eq!(p - Point3::ORIGIN, Vec3::new(3., 2., 1.));
+        assert_eq!(Point3::ORIGIN + v - v, Point3::ORIGIN);
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(Vec3::ZERO.normalize(), None);
+
+        let inv = m.inverse_affine().unwrap();
+        assert_eq!(&inv * p, Point3::new(1., 0., -1.));
+    }
+
+    #[test]
+    fn test_column_major() {
+        let m = Mat4::translation(Vec3::new(1., 2., 3.)) * Mat4::rotation(Vec3::Y, 0.5);
+        let cols = m.to_cols_f32();
+        assert_eq!(cols[3], [1., 2., 3., 1.]);
+        let flat = m.matrix().transpose().into_buffer().map(|x| x as f32);
+        assert_eq!(bytemuck::cast::<_, [f32; 16]>(cols), flat);
+    }
+}
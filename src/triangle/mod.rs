@@ -1,6 +1,7 @@
 use std::{
     f64::consts::PI,
-    time::{Instant, SystemTime, UNIX_EPOCH},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use bytemuck_derive::{Pod, Zeroable};
@@ -8,24 +9,89 @@ use num_traits::Float;
 use wgpu::util::DeviceExt;
 
 use crate::{
-    camera::{Camera, Heave, RotationalMovement, Surge, Sway, TranslationalMovement},
+    bounds::{Aabb, BoundingSphere, Obb},
+    camera::{
+        bookmark::{Bookmarks, Transition},
+        frustum::{CullStats, Frustum},
+        orbit::OrbitCamera,
+        path::{CameraPath, Keyframe, PathPlayback, Spline},
+        projection::{Projection, ViewVolume},
+        Camera, CameraController, Heave, Inertia, OrientationMode, Pace, RotationalMovement, Surge,
+        Sway, TranslationalMovement,
+    },
     delta_time::DeltaTime,
-    texture::{DepthBuffer, ImageSampler, ImageTexture},
-    transform::{perspective, rotate, translate},
+    input::{InputState, Position2D},
+    scene::{NodeId, SceneGraph},
+    skybox::Skybox,
+    texture::{
+        cubemap::face_direction, CubeTexture, DepthBuffer, ImageSampler, ImageTexture,
+        SamplerOptions, TextureOptions,
+    },
+    transform::{inverse_affine, DepthOrder, Mat4, Quaternion, TransformMatrix, Trs},
     Draw, DrawArgs, RenderApp, RenderContext, RenderInit, RenderInitArgs, RenderNextStep, Resize,
-    ResizeArgs, Update, UpdateArgs, WndSize,
+    ResizeArgs, Update, UpdateArgs, UpdateEvent, WndSize,
 };
 
 const SHADER: &str = include_str!("triangle.wgsl");
 const WALL: &[u8] = include_bytes!("wall.jpg");
+/// keeps the wall sharp on cube faces seen edge-on
+const WALL_ANISOTROPY: u16 = 16;
 const IS_WIREFRAME: bool = false;
+const DEPTH_ORDER: DepthOrder = DepthOrder::Reversed;
+/// held with a digit to save a bookmark; the left modifiers already steer the camera
+const BOOKMARK_SAVE_KEY: winit::keyboard::KeyCode = winit::keyboard::KeyCode::AltRight;
+/// seconds to fly to a restored bookmark; `None` jumps there at once
+const BOOKMARK_TRANSITION: Option<f64> = Some(0.75);
+/// radians per second while Q or E is held
+const ROLL_SPEED: f64 = PI / 2.;
+/// seconds between keyframes added with K
+const KEYFRAME_INTERVAL: f64 = 2.;
 const SIN_WAVE_X_PER_PERIOD: usize = 2 << 10;
+const MODEL_POSITIONS: [[f64; 3]; 10] = [
+    [0.0, 0.0, 0.0],
+    [2.0, 5.0, -15.0],
+    [-1.5, -2.2, -2.5],
+    [-3.8, -2.0, -12.3],
+    [2.4, -0.4, -3.5],
+    [-1.7, 3.0, -7.5],
+    [1.3, -2.0, -2.5],
+    [1.5, 2.0, -2.5],
+    [1.5, 0.2, -1.5],
+    [-1.3, 1.0, -1.5],
+];
+/// index into [`MODEL_POSITIONS`] of the cube the moon orbits
+const MOON_PARENT: usize = 4;
+const MOON_OFFSET: [f64; 3] = [0., 1.2, 0.];
+const MOON_SCALE: f64 = 0.4;
+/// texels along each edge of the generated sky faces
+const SKY_FACE_SIZE: u32 = 64;
 
 #[derive(Debug)]
-pub struct DrawTriangleInit {}
+pub struct DrawTriangleInit {
+    bookmarks: Bookmarks,
+    bookmarks_file: Option<PathBuf>,
+    camera_path_file: Option<PathBuf>,
+}
 impl DrawTriangleInit {
+    /// no bookmarks or camera path, and none written anywhere, so that replays stay deterministic
     pub fn new() -> Self {
-        Self {}
+        Self {
+            bookmarks: Bookmarks::new(),
+            bookmarks_file: None,
+            camera_path_file: None,
+        }
+    }
+    pub fn set_bookmarks(&mut self, bookmarks: Bookmarks) {
+        self.bookmarks = bookmarks;
+    }
+    /// where saving a bookmark writes all of them; leave unset while recording or replaying
+    pub fn set_bookmarks_file(&mut self, path: impl Into<PathBuf>) {
+        self.bookmarks_file = Some(path.into());
+    }
+    /// where keyframes are saved and an empty path is loaded from; leave unset while recording
+    /// or replaying
+    pub fn set_camera_path_file(&mut self, path: impl Into<PathBuf>) {
+        self.camera_path_file = Some(path.into());
     }
 }
 impl Default for DrawTriangleInit {
@@ -35,7 +101,12 @@ impl Default for DrawTriangleInit {
 }
 impl RenderInit for DrawTriangleInit {
     fn init(&self, args: RenderInitArgs<'_>) -> Box<dyn RenderApp> {
-        Box::new(DrawTriangle::new(args))
+        Box::new(DrawTriangle::new(
+            args,
+            self.bookmarks.clone(),
+            self.bookmarks_file.clone(),
+            self.camera_path_file.clone(),
+        ))
     }
 }
 
@@ -43,20 +114,51 @@ impl RenderInit for DrawTriangleInit {
 struct DrawTriangle {
     wnd_size: WndSize,
     depth_buffer: DepthBuffer,
+    skybox: Skybox,
     pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     index_count: u32,
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
-    camera: Camera,
+    free_camera: Camera,
+    orbit_camera: OrbitCamera,
+    camera_kind: CameraKind,
+    /// local bounds of the cube mesh
+    cube_bounds: Aabb,
+    scene: SceneGraph<Cube>,
+    /// one per [`MODEL_POSITIONS`]
+    cube_nodes: Vec<NodeId>,
+    /// spins the moon around its parent cube
+    moon_pivot: NodeId,
+    /// objects drawn and culled in the latest frame
+    cull_stats: CullStats,
+    camera_path: CameraPath,
+    /// `None` keeps the camera path in memory only
+    camera_path_file: Option<PathBuf>,
+    path_playback: Option<PathPlayback>,
+    bookmarks: Bookmarks,
+    /// `None` keeps saved bookmarks in memory only
+    bookmarks_file: Option<PathBuf>,
+    transition: Option<Transition>,
     draw_delta_time: DeltaTime,
 }
 impl DrawTriangle {
-    pub fn new(args: RenderInitArgs<'_>) -> Self {
-        let texture = ImageTexture::new(args.device, WALL, Some("wall"));
+    pub fn new(
+        args: RenderInitArgs<'_>,
+        bookmarks: Bookmarks,
+        bookmarks_file: Option<PathBuf>,
+        camera_path_file: Option<PathBuf>,
+    ) -> Self {
+        let texture =
+            ImageTexture::new(args.device, WALL, Some("wall")).expect("embedded texture decodes");
         texture.register(args.queue);
-        let sampler = ImageSampler::new(args.device, Some("sampler"));
+        let sampler = ImageSampler::with_options(
+            args.device,
+            SamplerOptions::anisotropic(WALL_ANISOTROPY),
+            Some("sampler"),
+        )
+        .expect("anisotropic options are valid");
         let shader = wgpu::ShaderSource::Wgsl(SHADER.into());
         let desc = wgpu::ShaderModuleDescriptor {
             label: None,
@@ -89,13 +191,11 @@ impl DrawTriangle {
             usage: wgpu::BufferUsages::INDEX,
         };
         let index_buffer = args.device.create_buffer_init(&desc);
-        let swap_chain = args.surface.get_capabilities(args.adapter);
-        let swap_chain = swap_chain.formats[0];
         let fragment = wgpu::FragmentState {
             module: &shader,
             entry_point: "fs_main",
             compilation_options: Default::default(),
-            targets: &[Some(swap_chain.into())],
+            targets: &[Some(args.surface_format.into())],
         };
         let desc = wgpu::BufferDescriptor {
             label: Some("uniform"),
@@ -160,6 +260,20 @@ impl DrawTriangle {
             push_constant_ranges: &[],
         };
         let layout = args.device.create_pipeline_layout(&desc);
+        let mut free_camera = Camera::new();
+        free_camera.set_projection(perspective_projection());
+        free_camera.set_look_smoothing(Some(0.03));
+        free_camera.set_inertia(Some(Inertia {
+            acceleration: 12.,
+            damping: 8.,
+        }));
+        // the pipelines compare depth in this order, so every projection must share it
+        let depth_buffer = DepthBuffer::new(
+            args.device,
+            args.wnd_size,
+            free_camera.projection().depth(),
+            Some("depth buffer"),
+        );
         let polygon_mode = if IS_WIREFRAME {
             wgpu::PolygonMode::Line
         } else {
@@ -173,13 +287,21 @@ impl DrawTriangle {
                 polygon_mode,
                 ..Default::default()
             },
-            depth_stencil: Some(DepthBuffer::state()),
+            depth_stencil: Some(depth_buffer.state()),
             multisample: wgpu::MultisampleState::default(),
             fragment: Some(fragment),
             multiview: None,
             cache: None,
         };
         let pipeline = args.device.create_render_pipeline(&desc);
+        let sky_options = TextureOptions {
+            mipmaps: false,
+            ..Default::default()
+        };
+        let sky = CubeTexture::from_images(args.device, sky_faces(), sky_options, Some("sky"))
+            .expect("generated sky faces are valid");
+        sky.register(args.queue);
+        let skybox = Skybox::new(args.device, sky, args.surface_format, &depth_buffer);
         let layout = pipeline.get_bind_group_layout(0);
         let desc = wgpu::BindGroupDescriptor {
             label: None,
@@ -190,23 +312,146 @@ impl DrawTriangle {
                 .collect::<Vec<_>>(),
         };
         let bind_group = args.device.create_bind_group(&desc);
-        let depth_buffer = DepthBuffer::new(args.device, args.wnd_size, Some("depth buffer"));
-        let camera = Camera::new();
-        let draw_delta_time = DeltaTime::new(Instant::now());
+        let mut orbit_camera = OrbitCamera::new();
+        orbit_camera.set_projection(perspective_projection());
+        let cube_bounds = mesh.aabb().expect("cube has vertices");
+        let (mut scene, cube_nodes, moon_pivot) = scene();
+        let bounds = scene_bounds(&mut scene, &cube_bounds);
+        let aspect = args.wnd_size.width as f64 / args.wnd_size.height as f64;
+        orbit_camera.frame(bounds.min, bounds.max, aspect);
+        let draw_delta_time = DeltaTime::new(args.time.instant);
         Self {
             wnd_size: args.wnd_size,
             depth_buffer,
+            skybox,
             pipeline,
             vertex_buffer,
             index_buffer,
             index_count: mesh.indices.len() as u32,
             uniform_buffer,
             bind_group,
-            camera,
+            free_camera,
+            orbit_camera,
+            camera_kind: CameraKind::Free,
+            cube_bounds,
+            scene,
+            cube_nodes,
+            moon_pivot,
+            cull_stats: CullStats::new(),
+            camera_path: CameraPath::new(Spline::CatmullRom),
+            camera_path_file,
+            path_playback: None,
+            bookmarks,
+            bookmarks_file,
+            transition: None,
             draw_delta_time,
         }
     }
 
+    fn camera(&self) -> &dyn CameraController {
+        match self.camera_kind {
+            CameraKind::Free => &self.free_camera,
+            CameraKind::Orbit => &self.orbit_camera,
+        }
+    }
+    fn camera_mut(&mut self) -> &mut dyn CameraController {
+        match self.camera_kind {
+            CameraKind::Free => &mut self.free_camera,
+            CameraKind::Orbit => &mut self.orbit_camera,
+        }
+    }
+    /// gives the active camera `projection`, reversing depth the way the depth buffer does
+    fn set_projection(&mut self, mut projection: Projection) {
+        projection.set_depth(self.depth_buffer.order());
+        self.camera_mut().set_projection(projection);
+    }
+
+    /// nearest cube under the cursor, or under the screen center while the cursor is captured
+    fn hovered_cube(&self, models: &[TransformMatrix], input: &InputState) -> Option<usize> {
+        let cursor = if input.is_cursor_captured() {
+            Position2D {
+                x: self.wnd_size.width as f64 / 2.,
+                y: self.wnd_size.height as f64 / 2.,
+            }
+        } else {
+            input.cursor_pos()?
+        };
+        let ray = self.camera().screen_ray(cursor, self.wnd_size)?;
+        models
+            .iter()
+            .enumerate()
+            .filter_map(|(i, model)| {
+                let local = ray.transform(&inverse_affine(model)?);
+                let t = local.intersect_aabb(self.cube_bounds.min, self.cube_bounds.max)?;
+                Some((i, t))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+
+    /// appends the free camera pose to the path and saves it
+    fn add_keyframe(&mut self) {
+        let time = match self.camera_path.keyframes().last() {
+            Some(last) => last.time + KEYFRAME_INTERVAL,
+            None => 0.,
+        };
+        let pose = self.free_camera.pose();
+        self.camera_path.insert(Keyframe { time, pose });
+        let Some(path) = &self.camera_path_file else {
+            return;
+        };
+        if let Err(e) = self.camera_path.save(path) {
+            tracing::warn!(?e, "failed to save camera path");
+        }
+    }
+    /// plays the recorded path, or the saved one if none has been recorded and a file is set
+    fn toggle_path_playback(&mut self) {
+        if self.path_playback.take().is_some() {
+            return;
+        }
+        if self.camera_path.keyframes().is_empty() {
+            let Some(file) = &self.camera_path_file else {
+                return;
+            };
+            match CameraPath::load(file) {
+                Ok(path) => self.camera_path = path,
+                Err(e) => {
+                    tracing::warn!(?e, "failed to load camera path");
+                    return;
+                }
+            }
+        }
+        self.camera_kind = CameraKind::Free;
+        self.path_playback = Some(PathPlayback::new(self.camera_path.clone(), 1.));
+    }
+
+    fn save_bookmark(&mut self, slot: u8) {
+        self.bookmarks.set(slot, self.free_camera.clone());
+        let Some(path) = &self.bookmarks_file else {
+            return;
+        };
+        if let Err(e) = self.bookmarks.save(path) {
+            tracing::warn!(?e, "failed to save camera bookmarks");
+        }
+    }
+    fn restore_bookmark(&mut self, slot: u8) {
+        let Some(mut target) = self.bookmarks.get(slot).cloned() else {
+            return;
+        };
+        // bookmarks from elsewhere may reverse depth differently
+        let mut projection = target.projection();
+        projection.set_depth(self.depth_buffer.order());
+        target.set_projection(projection);
+        self.camera_kind = CameraKind::Free;
+        self.path_playback = None;
+        match BOOKMARK_TRANSITION {
+            Some(duration) => {
+                self.transition = Some(Transition::new(&self.free_camera, target, duration));
+            }
+            None => self.free_camera = target,
+        }
+    }
+
     fn update_camera(&mut self, context: &RenderContext) {
         let Some(delta_time) = self.draw_delta_time.delta() else {
             return;
@@ -236,14 +481,55 @@ impl DrawTriangle {
             (true, false) => Some(Heave::Up),
             (false, true) => Some(Heave::Down),
         };
-        let movement = TranslationalMovement { surge, sway, heave };
-        self.camera.translate(movement, delta_time.as_secs_f64());
+        let is_control_left = context
+            .input
+            .is_key_pressed(winit::keyboard::KeyCode::ControlLeft);
+        let is_alt_left = context
+            .input
+            .is_key_pressed(winit::keyboard::KeyCode::AltLeft);
+        let pace = match (is_control_left, is_alt_left) {
+            (true, true) | (false, false) => None,
+            (true, false) => Some(Pace::Sprint),
+            (false, true) => Some(Pace::Slow),
+        };
+        let movement = TranslationalMovement {
+            surge,
+            sway,
+            heave,
+            pace,
+        };
+        self.camera_mut()
+            .translate(movement, delta_time.as_secs_f64());
+        self.camera_mut().advance(delta_time.as_secs_f64());
+        let is_q = context.input.is_key_pressed(winit::keyboard::KeyCode::KeyQ);
+        let is_e = context.input.is_key_pressed(winit::keyboard::KeyCode::KeyE);
+        let roll = match (is_q, is_e) {
+            (true, true) | (false, false) => 0.,
+            (true, false) => -ROLL_SPEED,
+            (false, true) => ROLL_SPEED,
+        };
+        if self.camera_kind == CameraKind::Free && roll != 0. {
+            self.free_camera.roll(roll * delta_time.as_secs_f64());
+        }
     }
 }
 impl Draw for DrawTriangle {
     fn draw(&mut self, args: DrawArgs<'_>) -> RenderNextStep {
-        self.draw_delta_time.update(Instant::now());
-        self.update_camera(args.context);
+        self.draw_delta_time.update(args.time.instant);
+        if let Some(transition) = &mut self.transition {
+            if !transition.apply(&mut self.free_camera, args.time.instant) {
+                self.transition = None;
+            }
+        } else if let Some(playback) = &mut self.path_playback {
+            if let Some(pose) = playback.pose(args.time.instant) {
+                self.free_camera.set_pose(pose);
+            }
+            if playback.is_finished(args.time.instant) {
+                self.path_playback = None;
+            }
+        } else {
+            self.update_camera(args.context);
+        }
         let gray = wgpu::Color {
             r: 0.2,
             g: 0.3,
@@ -258,7 +544,7 @@ impl Draw for DrawTriangle {
                 store: wgpu::StoreOp::Store,
             },
         };
-        let normalized_sin = normalized_sin();
+        let normalized_sin = normalized_sin(args.time.system);
         // let trans = {
         //     let translate = translate([0.5, -0.5, 0.0]);
         //     let angle = sin * PI * 2.;
@@ -286,31 +572,52 @@ impl Draw for DrawTriangle {
         // let radius = 10.;
         // let (sin, cos) = waves();
         // let view = look_at([sin * radius, 0., cos * radius], [0., 0., 0.], [0., 1., 0.]);
-        let view = self.camera.view_matrix();
+        let view = Mat4::from(self.camera().view_matrix());
         let aspect = self.wnd_size.width as f64 / self.wnd_size.height as f64;
-        let projection = perspective(self.camera.fov(), aspect, 0.1, 100.);
-
-        let model_positions = [
-            [0.0, 0.0, 0.0],
-            [2.0, 5.0, -15.0],
-            [-1.5, -2.2, -2.5],
-            [-3.8, -2.0, -12.3],
-            [2.4, -0.4, -3.5],
-            [-1.7, 3.0, -7.5],
-            [1.3, -2.0, -2.5],
-            [1.5, 2.0, -2.5],
-            [1.5, 0.2, -1.5],
-            [-1.3, 1.0, -1.5],
-        ];
-        let models = model_positions.into_iter().map(translate);
-        for (i, model_position) in models.enumerate() {
-            let rotate = rotate([1., 0.3, 0.5], normalized_sin * i as f64 * 20. * PI / 180.);
-            let model = model_position.mul_matrix_square(&rotate);
+        let clip = self.camera().projection().clip();
+        debug_assert_eq!(
+            self.camera().projection().depth(),
+            self.depth_buffer.order(),
+            "projection and depth buffer disagree on depth order"
+        );
+        let projection = Mat4::from(self.camera().projection().matrix(aspect));
+        let frustum = Frustum::new((&projection * &view).matrix(), clip.depth);
+
+        for (i, (&node, position)) in self.cube_nodes.iter().zip(MODEL_POSITIONS).enumerate() {
+            let angle = normalized_sin * i as f64 * 20. * PI / 180.;
+            let local = Trs {
+                translation: position,
+                rotation: Quaternion::from_axis_angle([1., 0.3, 0.5], angle),
+                scale: [1.; 3],
+            };
+            self.scene.set_local(node, local);
+        }
+        let orbit = Trs {
+            rotation: Quaternion::from_axis_angle([0., 1., 0.], normalized_sin * 2. * PI),
+            ..Trs::default()
+        };
+        self.scene.set_local(self.moon_pivot, orbit);
+        let models = self
+            .scene
+            .drawables()
+            .map(|(_, Cube, world)| world.clone())
+            .collect::<Vec<_>>();
+        let hovered = self.hovered_cube(&models, &args.context.input);
+
+        self.cull_stats = CullStats::new();
+        for (i, model) in models.into_iter().enumerate() {
+            let bounds = self.cube_bounds.transform(&model);
+            let is_visible = frustum.intersects_aabb(bounds.min, bounds.max);
+            self.cull_stats.count(is_visible);
+            if !is_visible {
+                continue;
+            }
             let uniform = Uniform {
-                model: model.transpose().into_buffer().map(|x| x as f32),
-                view: view.transpose().into_buffer().map(|x| x as f32),
-                projection: projection.transpose().into_buffer().map(|x| x as f32),
-                _padding: [0; 3],
+                model: Mat4::from(model).to_cols_f32(),
+                view: view.to_cols_f32(),
+                projection: projection.to_cols_f32(),
+                _padding: [0; 2],
+                is_highlighted: (hovered == Some(i)) as u32,
                 sin: normalized_sin as f32,
             };
             args.queue
@@ -344,6 +651,14 @@ impl Draw for DrawTriangle {
             }
             args.queue.submit([command.finish()]);
         }
+        self.skybox.draw(
+            args.device,
+            args.queue,
+            &args.view,
+            &self.depth_buffer,
+            &view,
+            &self.camera().projection(),
+        );
 
         RenderNextStep {
             should_request_redraw: true,
@@ -352,26 +667,115 @@ impl Draw for DrawTriangle {
 }
 impl Update for DrawTriangle {
     fn update(&mut self, args: UpdateArgs) -> RenderNextStep {
-        if let winit::event::WindowEvent::MouseWheel {
+        if let UpdateEvent::Window(winit::event::WindowEvent::MouseWheel {
             device_id: _,
             delta,
             phase: _,
-        } = &args.event
+        }) = &args.event
         {
             let y = match delta {
                 winit::event::MouseScrollDelta::LineDelta(_, y) => *y as f64,
                 winit::event::MouseScrollDelta::PixelDelta(pos) => pos.y,
             };
             let scale_to_radian = (2.0_f64).powi(7);
-            self.camera.zoom(y / scale_to_radian);
+            self.camera_mut().zoom(y / scale_to_radian);
         }
-        if let Some(cursor_change) = args.context.input.cursor_change() {
-            let scale_to_radian = (2.0_f64).powi(4);
-            let movement = RotationalMovement {
-                yaw: cursor_change.x / scale_to_radian,
-                pitch: -cursor_change.y / scale_to_radian,
-            };
-            self.camera.rotate(movement);
+        let look = match &args.event {
+            UpdateEvent::Window(winit::event::WindowEvent::CursorMoved { .. })
+                if !args.context.input.is_cursor_captured() =>
+            {
+                args.context.input.cursor_change()
+            }
+            UpdateEvent::Device(winit::event::DeviceEvent::MouseMotion { .. })
+                if args.context.input.is_cursor_captured() =>
+            {
+                args.context.input.mouse_motion()
+            }
+            _ => None,
+        };
+        if let Some(look) = look {
+            if args
+                .context
+                .input
+                .is_mouse_pressed(winit::event::MouseButton::Middle)
+            {
+                self.camera_mut().pan(look);
+            } else {
+                let scale_to_radian = (2.0_f64).powi(4);
+                let movement = RotationalMovement {
+                    yaw: look.x / scale_to_radian,
+                    pitch: -look.y / scale_to_radian,
+                };
+                self.camera_mut().rotate(movement);
+            }
+        }
+        if let UpdateEvent::Window(
+            winit::event::WindowEvent::Touch(_)
+            | winit::event::WindowEvent::PinchGesture { .. }
+            | winit::event::WindowEvent::PanGesture { .. },
+        ) = &args.event
+        {
+            if let Some(pinch) = args.context.input.pinch() {
+                let scale_to_radian = 2.0_f64;
+                self.camera_mut().zoom(pinch / scale_to_radian);
+            }
+            if let Some(pan) = args.context.input.pan() {
+                let scale_to_radian = (2.0_f64).powi(4);
+                let movement = RotationalMovement {
+                    yaw: pan.x / scale_to_radian,
+                    pitch: -pan.y / scale_to_radian,
+                };
+                self.camera_mut().rotate(movement);
+            }
+        }
+        if let UpdateEvent::Key {
+            key,
+            state: winit::event::ElementState::Pressed,
+            repeat: false,
+        } = &args.event
+        {
+            if let Some(slot) = bookmark_slot(*key) {
+                let is_saving = args.context.input.is_key_pressed(BOOKMARK_SAVE_KEY);
+                if is_saving {
+                    self.save_bookmark(slot);
+                } else {
+                    self.restore_bookmark(slot);
+                }
+            }
+            match key {
+                winit::keyboard::KeyCode::KeyC => {
+                    self.camera_kind = match self.camera_kind {
+                        CameraKind::Free => CameraKind::Orbit,
+                        CameraKind::Orbit => CameraKind::Free,
+                    };
+                }
+                winit::keyboard::KeyCode::KeyF => {
+                    let bounds = scene_bounds(&mut self.scene, &self.cube_bounds);
+                    let aspect = self.wnd_size.width as f64 / self.wnd_size.height as f64;
+                    self.camera_mut().frame(bounds.min, bounds.max, aspect);
+                }
+                winit::keyboard::KeyCode::KeyP => {
+                    let projection = match self.camera().projection().volume() {
+                        ViewVolume::Perspective { .. } => orthographic_projection(),
+                        ViewVolume::Orthographic { .. } => perspective_projection(),
+                    };
+                    self.set_projection(projection);
+                }
+                winit::keyboard::KeyCode::KeyO => {
+                    let mode = match self.free_camera.orientation_mode() {
+                        OrientationMode::YawPitch => OrientationMode::Quaternion,
+                        OrientationMode::Quaternion => OrientationMode::YawPitch,
+                    };
+                    self.free_camera.set_orientation_mode(mode);
+                }
+                winit::keyboard::KeyCode::KeyI => {
+                    let invert_y = !self.free_camera.invert_y();
+                    self.free_camera.set_invert_y(invert_y);
+                }
+                winit::keyboard::KeyCode::KeyK => self.add_keyframe(),
+                winit::keyboard::KeyCode::KeyL => self.toggle_path_playback(),
+                _ => (),
+            }
         }
         RenderNextStep {
             should_request_redraw: false,
@@ -381,7 +785,8 @@ impl Update for DrawTriangle {
 impl Resize for DrawTriangle {
     fn resize(&mut self, args: ResizeArgs<'_>) -> RenderNextStep {
         self.wnd_size = args.size;
-        self.depth_buffer = DepthBuffer::new(args.device, args.size, Some("depth buffer"));
+        let order = self.depth_buffer.order();
+        self.depth_buffer = DepthBuffer::new(args.device, args.size, order, Some("depth buffer"));
         RenderNextStep {
             should_request_redraw: false,
         }
@@ -389,13 +794,98 @@ impl Resize for DrawTriangle {
 }
 impl RenderApp for DrawTriangle {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CameraKind {
+    Free,
+    Orbit,
+}
+
+fn perspective_projection() -> Projection {
+    let mut projection = Projection::perspective(PI / 4., 0.1, None);
+    projection.set_depth(DEPTH_ORDER);
+    projection
+}
+fn orthographic_projection() -> Projection {
+    let mut projection = Projection::orthographic(10., 0.1, 100.);
+    projection.set_depth(DEPTH_ORDER);
+    projection
+}
+
+/// slots 1 to 9 on the digit row
+fn bookmark_slot(key: winit::keyboard::KeyCode) -> Option<u8> {
+    Some(match key {
+        winit::keyboard::KeyCode::Digit1 => 1,
+        winit::keyboard::KeyCode::Digit2 => 2,
+        winit::keyboard::KeyCode::Digit3 => 3,
+        winit::keyboard::KeyCode::Digit4 => 4,
+        winit::keyboard::KeyCode::Digit5 => 5,
+        winit::keyboard::KeyCode::Digit6 => 6,
+        winit::keyboard::KeyCode::Digit7 => 7,
+        winit::keyboard::KeyCode::Digit8 => 8,
+        winit::keyboard::KeyCode::Digit9 => 9,
+        _ => return None,
+    })
+}
+
+/// drawable marker for the textured cube mesh
+#[derive(Debug, Clone, Copy)]
+struct Cube;
+
+/// a vertical gradient from the ground through the horizon to the zenith
+fn sky_faces() -> [image::DynamicImage; 6] {
+    let ground = [0.30, 0.28, 0.25];
+    let horizon = [0.85, 0.90, 0.95];
+    let zenith = [0.25, 0.45, 0.80];
+    core::array::from_fn(|face| {
+        let face = image::RgbaImage::from_fn(SKY_FACE_SIZE, SKY_FACE_SIZE, |x, y| {
+            let u = (x as f64 + 0.5) / SKY_FACE_SIZE as f64;
+            let v = (y as f64 + 0.5) / SKY_FACE_SIZE as f64;
+            let [dx, dy, dz] = face_direction(face as u32, u, v);
+            let height = dy / (dx * dx + dy * dy + dz * dz).sqrt();
+            let (from, to, t) = if height < 0. {
+                (horizon, ground, (-height * 8.).min(1.))
+            } else {
+                (horizon, zenith, height)
+            };
+            let [r, g, b] =
+                core::array::from_fn(|i| ((from[i] + (to[i] - from[i]) * t) * 255.).round() as u8);
+            image::Rgba([r, g, b, 255])
+        });
+        face.into()
+    })
+}
+/// the [`MODEL_POSITIONS`] cubes, one of them with a moon
+fn scene() -> (SceneGraph<Cube>, Vec<NodeId>, NodeId) {
+    let mut scene = SceneGraph::new();
+    let cube_nodes = MODEL_POSITIONS
+        .into_iter()
+        .map(|position| scene.insert(None, Trs::from_translation(position), Some(Cube)))
+        .collect::<Vec<_>>();
+    let moon_pivot = scene.insert(Some(cube_nodes[MOON_PARENT]), Trs::default(), None);
+    let moon = Trs {
+        scale: [MOON_SCALE; 3],
+        ..Trs::from_translation(MOON_OFFSET)
+    };
+    scene.insert(Some(moon_pivot), moon, Some(Cube));
+    (scene, cube_nodes, moon_pivot)
+}
+/// world bounds of every cube in `scene`
+fn scene_bounds(scene: &mut SceneGraph<Cube>, cube: &Aabb) -> Aabb {
+    scene
+        .drawables()
+        .map(|(_, Cube, world)| cube.transform(world))
+        .reduce(|a, b| a.merge(&b))
+        .expect("scene has cubes")
+}
+
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 struct Uniform {
-    pub model: [f32; 16],
-    pub view: [f32; 16],
-    pub projection: [f32; 16],
-    pub _padding: [u32; 3],
+    pub model: [[f32; 4]; 4],
+    pub view: [[f32; 4]; 4],
+    pub projection: [[f32; 4]; 4],
+    pub _padding: [u32; 2],
+    pub is_highlighted: u32,
     pub sin: f32,
 }
 
@@ -421,6 +911,23 @@ struct Mesh {
     pub vertices: Vec<VertexAttributes>,
     pub indices: Vec<u32>,
 }
+impl Mesh {
+    pub fn positions(&self) -> impl Iterator<Item = [f64; 3]> + '_ {
+        self.vertices.iter().map(|v| v.position.map(f64::from))
+    }
+    /// `None` without vertices
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.positions())
+    }
+    #[allow(unused)]
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        BoundingSphere::from_points(self.positions())
+    }
+    #[allow(unused)]
+    pub fn obb(&self) -> Option<Obb> {
+        Obb::from_points(self.positions())
+    }
+}
 
 #[allow(unused)]
 fn triangle() -> Mesh {
@@ -693,8 +1200,8 @@ impl QuadVertexPos {
     }
 }
 
-fn normalized_sin() -> f64 {
-    let (sin, _) = waves();
+fn normalized_sin(now: SystemTime) -> f64 {
+    let (sin, _) = waves(now);
     normalize_neg_pos_1(sin)
 }
 fn normalize_neg_pos_1<T: Float>(v: T) -> T {
@@ -703,14 +1210,183 @@ fn normalize_neg_pos_1<T: Float>(v: T) -> T {
     (v + one) / two
 }
 
-fn waves() -> (f64, f64) {
-    let x = (SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis()
-        % (SIN_WAVE_X_PER_PERIOD as u128)) as f64
+fn waves(now: SystemTime) -> (f64, f64) {
+    let x = (now.duration_since(UNIX_EPOCH).unwrap().as_millis() % (SIN_WAVE_X_PER_PERIOD as u128))
+        as f64
         * 2.
         * PI
         / SIN_WAVE_X_PER_PERIOD as f64;
     (x.sin(), x.cos())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        gpu::{adapter, device, instance},
+        record::{replay_headless, RecordEvent, Recorder, Replay},
+        texture::RenderTarget,
+        FrameTime,
+    };
+
+    use super::*;
+
+    /// input as the window delivers it
+    enum LiveEvent {
+        Window(winit::event::WindowEvent),
+        Key(winit::keyboard::KeyCode),
+        MouseMotion(Position2D),
+        Frame,
+    }
+
+    /// Drives a [`DrawTriangle`] the way [`crate::wnd::Wnd`] does, recording as it goes.
+    ///
+    /// `events` are in milliseconds since init.
+    async fn live(events: Vec<(u64, LiveEvent)>) -> (Box<DrawTriangle>, String) {
+        const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let instance = instance();
+        let adapter = adapter(&instance, None).await.unwrap();
+        let (device, queue) = device(&adapter).await.unwrap();
+        let size = WndSize {
+            width: 64,
+            height: 48,
+        };
+        let target = RenderTarget::new(&device, size, &[FORMAT], None, None).unwrap();
+        let mut recorder = Recorder::new(vec![]);
+        let init = FrameTime::now();
+        let args = RenderInitArgs {
+            device: &device,
+            surface_format: FORMAT,
+            adapter: &adapter,
+            queue: &queue,
+            wnd_size: size,
+            time: init,
+        };
+        let mut app = Box::new(DrawTriangle::new(args, Bookmarks::new(), None, None));
+        let mut context = RenderContext::new();
+        recorder.record(init, RecordEvent::Init(size)).unwrap();
+        for (ms, event) in events {
+            let time = FrameTime {
+                instant: init.instant + Duration::from_millis(ms),
+                system: init.system + Duration::from_millis(ms),
+            };
+            let event = match event {
+                LiveEvent::Window(event) => {
+                    context.input.update_window_event(&event);
+                    let is_click = matches!(
+                        event,
+                        winit::event::WindowEvent::MouseInput {
+                            state: winit::event::ElementState::Pressed,
+                            button: winit::event::MouseButton::Left,
+                            ..
+                        }
+                    );
+                    if is_click && !context.input.is_cursor_captured() {
+                        context.input.set_cursor_captured(true);
+                        let record = RecordEvent::CursorCaptured(true);
+                        recorder.record(time, record).unwrap();
+                    }
+                    let record = RecordEvent::from_window(&event).unwrap();
+                    recorder.record(time, record).unwrap();
+                    UpdateEvent::Window(event)
+                }
+                LiveEvent::Key(key) => {
+                    let state = winit::event::ElementState::Pressed;
+                    let record = RecordEvent::Key {
+                        key,
+                        state,
+                        repeat: false,
+                    };
+                    recorder.record(time, record).unwrap();
+                    context.input.update_key_code(key, state);
+                    UpdateEvent::Key {
+                        key,
+                        state,
+                        repeat: false,
+                    }
+                }
+                LiveEvent::MouseMotion(delta) => {
+                    let record = RecordEvent::MouseMotion(delta);
+                    recorder.record(time, record).unwrap();
+                    context.input.update_mouse_motion(delta);
+                    UpdateEvent::Device(winit::event::DeviceEvent::MouseMotion {
+                        delta: (delta.x, delta.y),
+                    })
+                }
+                LiveEvent::Frame => {
+                    recorder.record(time, RecordEvent::Frame).unwrap();
+                    let args = DrawArgs {
+                        view: target.create_color_view(0),
+                        device: &device,
+                        queue: &queue,
+                        context: &context,
+                        time,
+                    };
+                    app.draw(args);
+                    device.poll(wgpu::Maintain::Wait);
+                    continue;
+                }
+            };
+            let args = UpdateArgs {
+                event,
+                context: &context,
+            };
+            app.update(args);
+        }
+        let recording = String::from_utf8(recorder.into_inner()).unwrap();
+        (app, recording)
+    }
+
+    /// looks around with the cursor, clicks to capture it, then flies forward while turning
+    fn events() -> Vec<(u64, LiveEvent)> {
+        let device_id = winit::event::DeviceId::dummy();
+        let cursor = |x, y| winit::event::WindowEvent::CursorMoved {
+            device_id,
+            position: winit::dpi::PhysicalPosition::new(x, y),
+        };
+        let mut events = vec![
+            (1, LiveEvent::Window(cursor(10., 10.))),
+            (2, LiveEvent::Window(cursor(14., 9.))),
+            (3, LiveEvent::Frame),
+            (
+                4,
+                LiveEvent::Window(winit::event::WindowEvent::MouseInput {
+                    device_id,
+                    state: winit::event::ElementState::Pressed,
+                    button: winit::event::MouseButton::Left,
+                }),
+            ),
+            (5, LiveEvent::Key(winit::keyboard::KeyCode::KeyW)),
+        ];
+        for i in 1..30 {
+            let ms = i * 7 + i % 3;
+            let delta = Position2D {
+                x: 3. + i as f64,
+                y: -2.,
+            };
+            events.push((ms, LiveEvent::MouseMotion(delta)));
+            events.push((ms + 1, LiveEvent::Frame));
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_replay_is_deterministic() {
+        let (live, recording) = live(events()).await;
+        let replay = Replay::read(recording.as_bytes()).unwrap();
+        let mut replayed = replay_headless(replay, |args| {
+            Box::new(DrawTriangle::new(args, Bookmarks::new(), None, None))
+        })
+        .await
+        .unwrap();
+        assert_eq!(replayed.free_camera, live.free_camera);
+        assert_ne!(live.free_camera.position(), Camera::new().position());
+        assert_eq!(replayed.cull_stats, live.cull_stats);
+        let stats = replayed.cull_stats;
+        assert_eq!(
+            stats.drawn + stats.culled,
+            replayed.scene.drawables().count()
+        );
+    }
+}
@@ -2,7 +2,8 @@ struct Uniform {
     model: mat4x4<f32>,
     view: mat4x4<f32>,
     projection: mat4x4<f32>,
-    padding: vec3<u32>,
+    padding: vec2<u32>,
+    is_highlighted: u32,
     sin: f32,
 }
 @group(0)
@@ -60,5 +61,8 @@ fn fs_main(fragment: Fragment) -> Color {
     let tex_color = textureSample(texture, sampl, fragment.tex_coord);
     var color = Color();
     color.color = solid_color * tex_color;
+    if uniform.is_highlighted != 0u {
+        color.color = mix(color.color, vec4<f32>(1.0, 1.0, 1.0, 1.0), 0.5);
+    }
     return color;
 }
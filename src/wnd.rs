@@ -1,32 +1,56 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Context;
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{DeviceEvent, DeviceId, WindowEvent},
     event_loop::ActiveEventLoop,
-    window::{Window, WindowId},
+    window::{CursorGrabMode, Window, WindowId},
 };
 
 use crate::{
     gpu::{adapter, device, instance},
     input::Position2D,
-    DrawArgs, RenderApp, RenderContext, RenderInit, RenderInitArgs, RenderNextStep, ResizeArgs,
-    UpdateArgs, WndSize,
+    record::{RecordEvent, Recorder, Replay, ReplayStep},
+    texture::readback::read_rgba8,
+    DrawArgs, FrameTime, RenderApp, RenderContext, RenderInit, RenderInitArgs, RenderNextStep,
+    ResizeArgs, UpdateArgs, UpdateEvent, WndSize,
 };
 
+/// saves the next frame as a PNG in the working directory
+const SCREENSHOT_KEY: winit::keyboard::KeyCode = winit::keyboard::KeyCode::F12;
+
 #[derive(Debug)]
 pub struct Wnd {
     app: Option<Box<dyn RenderInit>>,
     viewer: Option<ActiveWnd>,
+    cursor_capture: CursorCapture,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
 }
 impl Wnd {
     pub fn new(view: Box<dyn RenderInit>) -> Self {
         Self {
             viewer: None,
             app: Some(view),
+            cursor_capture: CursorCapture::Locked,
+            recorder: None,
+            replay: None,
         }
     }
+    pub fn set_cursor_capture(&mut self, mode: CursorCapture) {
+        self.cursor_capture = mode;
+    }
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+    /// live input is ignored until the replay runs out
+    pub fn set_replay(&mut self, replay: Replay) {
+        self.replay = Some(replay);
+    }
 }
 impl ApplicationHandler for Wnd {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
@@ -36,11 +60,28 @@ impl ApplicationHandler for Wnd {
             .unwrap();
         let window = Arc::new(window);
         let instance = instance();
-        let view = ActiveWnd::new(window, &instance, self.app.take().unwrap());
+        let options = ActiveWndOptions {
+            cursor_capture: self.cursor_capture,
+            recorder: self.recorder.take(),
+            replay: self.replay.take(),
+        };
+        let view = ActiveWnd::new(window, &instance, self.app.take().unwrap(), options);
         let view = pollster::block_on(view).unwrap();
         self.viewer = Some(view);
     }
 
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        let Some(viewer) = self.viewer.as_mut() else {
+            return;
+        };
+        viewer.device_update(event);
+    }
+
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -73,12 +114,18 @@ struct ActiveWnd {
     queue: wgpu::Queue,
     app: Box<dyn RenderApp>,
     context: RenderContext,
+    cursor_capture: CursorCapture,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    /// save the next drawn frame
+    is_screenshot_requested: bool,
 }
 impl ActiveWnd {
     pub async fn new<A>(
         window: Arc<winit::window::Window>,
         instance: &wgpu::Instance,
         app: Box<A>,
+        options: ActiveWndOptions,
     ) -> anyhow::Result<Self>
     where
         A: RenderInit + ?Sized,
@@ -93,16 +140,27 @@ impl ActiveWnd {
             .await
             .context("no adapter")?;
         let (device, queue) = device(&adapter).await?;
+        let surface_format = surface.get_capabilities(&adapter).formats[0];
+        let time = match &options.replay {
+            Some(replay) => {
+                let size = replay.wnd_size();
+                let _ = window
+                    .request_inner_size(winit::dpi::PhysicalSize::new(size.width, size.height));
+                replay.init_time()
+            }
+            None => FrameTime::now(),
+        };
         let args = RenderInitArgs {
             device: &device,
-            surface: &surface,
+            surface_format,
             adapter: &adapter,
             queue: &queue,
             wnd_size: size,
+            time,
         };
         let app = app.init(args);
         let context = RenderContext::new();
-        Ok(Self {
+        let mut this = Self {
             window,
             surface,
             adapter,
@@ -110,22 +168,34 @@ impl ActiveWnd {
             queue,
             app,
             context,
-        })
+            cursor_capture: options.cursor_capture,
+            recorder: options.recorder,
+            replay: options.replay,
+            is_screenshot_requested: false,
+        };
+        this.record(time, RecordEvent::Init(size));
+        Ok(this)
     }
 
     pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
-        let Some(config) = self
-            .surface
-            .get_default_config(&self.adapter, size.width, size.height)
+        let Some(mut config) =
+            self.surface
+                .get_default_config(&self.adapter, size.width, size.height)
         else {
             return;
         };
+        let usages = self.surface.get_capabilities(&self.adapter).usages;
+        if usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            // for screenshots
+            config.usage |= wgpu::TextureUsages::COPY_SRC;
+        }
         self.surface.configure(&self.device, &config);
         self.window.request_redraw();
         let size = WndSize {
             width: size.width,
             height: size.height,
         };
+        self.record(FrameTime::now(), RecordEvent::Resize(size));
         let args = ResizeArgs {
             device: &self.device,
             size,
@@ -136,34 +206,120 @@ impl ActiveWnd {
     }
 
     pub fn update(&mut self, event: winit::event::WindowEvent) {
-        if let winit::event::WindowEvent::KeyboardInput {
-            device_id: _,
+        if self.replay.is_some() {
+            return;
+        }
+        self.context.input.update_window_event(&event);
+        match &event {
+            winit::event::WindowEvent::MouseInput {
+                device_id: _,
+                state: winit::event::ElementState::Pressed,
+                button: winit::event::MouseButton::Left,
+            } => self.capture_cursor(),
+            winit::event::WindowEvent::KeyboardInput {
+                device_id: _,
+                event,
+                is_synthetic: _,
+            } if event.physical_key
+                == winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Escape) =>
+            {
+                self.release_cursor()
+            }
+            winit::event::WindowEvent::KeyboardInput {
+                device_id: _,
+                event,
+                is_synthetic: _,
+            } if event.physical_key == winit::keyboard::PhysicalKey::Code(SCREENSHOT_KEY)
+                && event.state == winit::event::ElementState::Pressed
+                && !event.repeat =>
+            {
+                self.is_screenshot_requested = true;
+                self.window.request_redraw();
+            }
+            winit::event::WindowEvent::Focused(false) => self.release_cursor(),
+            _ => (),
+        }
+        // after any capture change, since the app sees the event with the new capture state
+        if let Some(record) = RecordEvent::from_window(&event) {
+            self.record(FrameTime::now(), record);
+        }
+        let event = match event {
+            winit::event::WindowEvent::KeyboardInput {
+                device_id: _,
+                event:
+                    winit::event::KeyEvent {
+                        physical_key: winit::keyboard::PhysicalKey::Code(key),
+                        state,
+                        repeat,
+                        ..
+                    },
+                is_synthetic: _,
+            } => UpdateEvent::Key { key, state, repeat },
+            event => UpdateEvent::Window(event),
+        };
+        let args = UpdateArgs {
             event,
-            is_synthetic: _,
-        } = &event
-        {
-            self.context.input.update_key(event);
-        }
-        if let winit::event::WindowEvent::CursorMoved {
-            device_id: _,
-            position,
-        } = &event
-        {
-            let pos = Position2D {
-                x: position.x,
-                y: position.y,
+            context: &self.context,
+        };
+        let next = self.app.update(args);
+        self.handle_next(next);
+    }
+
+    pub fn device_update(&mut self, event: winit::event::DeviceEvent) {
+        if self.replay.is_some() {
+            return;
+        }
+        if let Some(record) = RecordEvent::from_device(&event) {
+            self.record(FrameTime::now(), record);
+        }
+        if let winit::event::DeviceEvent::MouseMotion { delta } = &event {
+            let delta = Position2D {
+                x: delta.0,
+                y: delta.1,
             };
-            self.context.input.update_cursor(pos);
+            self.context.input.update_mouse_motion(delta);
         }
         let args = UpdateArgs {
-            event,
+            event: UpdateEvent::Device(event),
             context: &self.context,
         };
         let next = self.app.update(args);
         self.handle_next(next);
     }
 
+    fn capture_cursor(&mut self) {
+        if self.context.input.is_cursor_captured() {
+            return;
+        }
+        let (preferred, fallback) = match self.cursor_capture {
+            CursorCapture::Confined => (CursorGrabMode::Confined, CursorGrabMode::Locked),
+            CursorCapture::Locked => (CursorGrabMode::Locked, CursorGrabMode::Confined),
+        };
+        let res = self
+            .window
+            .set_cursor_grab(preferred)
+            .or_else(|_| self.window.set_cursor_grab(fallback));
+        if let Err(e) = res {
+            tracing::warn!(?e, "failed to grab cursor");
+            return;
+        }
+        self.window.set_cursor_visible(false);
+        self.context.input.set_cursor_captured(true);
+        self.record(FrameTime::now(), RecordEvent::CursorCaptured(true));
+    }
+    fn release_cursor(&mut self) {
+        if !self.context.input.is_cursor_captured() {
+            return;
+        }
+        let _ = self.window.set_cursor_grab(CursorGrabMode::None);
+        self.window.set_cursor_visible(true);
+        self.context.input.set_cursor_captured(false);
+        self.record(FrameTime::now(), RecordEvent::CursorCaptured(false));
+    }
+
     pub fn draw(&mut self) -> anyhow::Result<()> {
+        let time = self.replay_until_frame().unwrap_or_else(FrameTime::now);
+        self.record(time, RecordEvent::Frame);
         let frame = self.surface.get_current_texture()?;
         let desc = wgpu::TextureViewDescriptor::default();
         let view = frame.texture.create_view(&desc);
@@ -172,16 +328,76 @@ impl ActiveWnd {
             device: &self.device,
             queue: &self.queue,
             context: &self.context,
+            time,
         };
         let next = self.app.draw(args);
+        if self.is_screenshot_requested {
+            self.is_screenshot_requested = false;
+            if let Err(e) = self.save_screenshot(&frame.texture) {
+                tracing::warn!(?e, "failed to save screenshot");
+            }
+        }
         frame.present();
         self.handle_next(next);
         Ok(())
     }
 
+    fn save_screenshot(&self, texture: &wgpu::Texture) -> anyhow::Result<()> {
+        let image = read_rgba8(&self.device, &self.queue, texture)?;
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let path = format!("screenshot_{}.png", since_epoch.as_millis());
+        image.save(&path)?;
+        tracing::info!(path, "saved screenshot");
+        Ok(())
+    }
+
+    /// `None` once there is nothing left to replay
+    fn replay_until_frame(&mut self) -> Option<FrameTime> {
+        let replay = self.replay.as_mut()?;
+        loop {
+            match replay.step(self.app.as_mut(), &mut self.context) {
+                Some(ReplayStep::Resize(size)) => {
+                    let size = winit::dpi::PhysicalSize::new(size.width, size.height);
+                    let _ = self.window.request_inner_size(size);
+                }
+                Some(ReplayStep::Frame(time)) => return Some(time),
+                None => {
+                    tracing::info!("replay finished");
+                    self.replay = None;
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, time: FrameTime, event: RecordEvent) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        if let Err(e) = recorder.record(time, event) {
+            tracing::warn!(?e, "failed to record");
+        }
+    }
+
     fn handle_next(&mut self, next: RenderNextStep) {
         if next.should_request_redraw {
             self.window.request_redraw();
         }
     }
 }
+
+#[derive(Debug)]
+struct ActiveWndOptions {
+    cursor_capture: CursorCapture,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+}
+
+/// how the cursor is held while mouse-look is active; falls back to the other mode if unsupported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorCapture {
+    /// cursor is kept inside the window
+    Confined,
+    /// cursor is pinned in place
+    Locked,
+}